@@ -1,5 +1,29 @@
+mod blit;
+mod draw;
+mod html;
+mod parse;
 #[cfg(feature = "image")]
-use crate::dithering::Ditherer;
+mod raster;
+mod resample;
+#[cfg(feature = "image")]
+mod shade;
+mod svg;
+mod transform;
+
+pub use blit::BlitOp;
+#[cfg(feature = "image")]
+pub use raster::RenderOptions;
+pub use resample::ResizeMethod;
+#[cfg(feature = "image")]
+pub use shade::Coverage;
+pub use svg::SvgOptions;
+
+use crate::color::{AnsiColor, CellColor, ColorMode};
+#[cfg(feature = "image")]
+use crate::{
+    convert::{Converter, Raise},
+    dithering::Ditherer,
+};
 
 /// this is just all 256 braille characters, with the raised dots meaning each
 /// of the following bits, with 0 being the least significant:
@@ -50,8 +74,23 @@ pub const BRAILLE_CHARS: [char; 256] = [
 const BRAILLE_LEN: usize = BRAILLE_CHARS[0].len_utf8();
 
 #[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
 pub enum Error {
+    /// the given x and y were outside of an image with the given width and
+    /// height, all in dots, or in characters for methods that take a column
+    /// and row like [BrailleImg::set_cell_color]
     OutOfBounds(u32, u32, u32, u32),
+    /// a character that isn't braille was found while parsing, at the given
+    /// column and row of characters
+    InvalidChar(char, u32, u32),
+    /// there were no braille characters to parse
+    Empty,
+    /// an image would have been created with the given width and height in
+    /// dots, where at least one of them is 0
+    ZeroDimension(u32, u32),
+    /// an image with the given width and height in dots would have too many
+    /// characters to be stored
+    TooLarge(u32, u32),
 }
 
 impl std::error::Error for Error {}
@@ -62,12 +101,31 @@ impl std::fmt::Display for Error {
             Error::OutOfBounds(x, y, w, h) => {
                 write!(f, "the coordinates (x: {x}, y: {y}) were outside the bounds of the BrailleImg (width: {w}, height: {h})")
             }
+            Error::InvalidChar(c, col, row) => {
+                write!(
+                    f,
+                    "the character {c:?} (column: {col}, row: {row}) is not a braille character"
+                )
+            }
+            Error::Empty => write!(f, "there were no braille characters to parse"),
+            Error::ZeroDimension(w, h) => {
+                write!(f, "a BrailleImg can't be empty (width: {w}, height: {h})")
+            }
+            Error::TooLarge(w, h) => {
+                write!(
+                    f,
+                    "a BrailleImg can't be this large (width: {w}, height: {h})"
+                )
+            }
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BrailleImg {
     braille_vals: Vec<u8>,
+    /// per-character colors, only allocated once any color is set
+    colors: Option<Vec<CellColor>>,
     dot_width: u32,
     dot_height: u32,
     char_width: u32,
@@ -78,25 +136,54 @@ impl BrailleImg {
     /// create a new [BrailleImg] with `width` and `height` dimensions, in dots,
     /// where each character is 2 dots wide and 4 dots tall
     /// # Panics
-    /// if either width or height is 0
+    /// if either width or height is 0, or the image would be too large, see
+    /// [BrailleImg::try_new] for a version that doesn't panic
     pub fn new(width: u32, height: u32) -> Self {
-        assert!(
-            width != 0 && height != 0,
-            "width and height must be greater than 0"
-        );
-        let x_size = width / 2 + (width % 2);
-        let extra_row = if height.is_multiple_of(4) { 1 } else { 0 };
-        let y_size = height / 4 + extra_row;
+        Self::try_new(width, height).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// checks if an image that is `width` by `height` dots could be created
+    /// without allocating it, returning its width and height in characters
+    /// # Errors
+    /// the same as [BrailleImg::try_new], except that whether the image can be
+    /// allocated isn't known yet
+    pub fn check_dimensions(width: u32, height: u32) -> Result<(u32, u32), Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::ZeroDimension(width, height));
+        }
+        let x_size = width.div_ceil(2);
+        let y_size = height.div_ceil(4);
+
+        x_size
+            .checked_mul(y_size)
+            .and_then(|len| usize::try_from(len).ok())
+            .filter(|&len| len <= isize::MAX as usize)
+            .ok_or(Error::TooLarge(width, height))?;
+        Ok((x_size, y_size))
+    }
 
-        let vals = vec![0; (x_size * y_size) as usize];
+    /// same as [BrailleImg::new], but returns an error for dimensions that
+    /// can't be used, which is useful for sizes that come from untrusted input
+    /// # Errors
+    /// - [Error::ZeroDimension] if either width or height is 0
+    /// - [Error::TooLarge] if the number of characters doesn't fit in a
+    ///   [u32] or can't be allocated
+    pub fn try_new(width: u32, height: u32) -> Result<Self, Error> {
+        let (x_size, y_size) = Self::check_dimensions(width, height)?;
+        let len = (x_size * y_size) as usize;
+        let mut vals = Vec::new();
+        vals.try_reserve_exact(len)
+            .map_err(|_| Error::TooLarge(width, height))?;
+        vals.resize(len, 0);
 
-        BrailleImg {
+        Ok(BrailleImg {
             braille_vals: vals,
+            colors: None,
             dot_width: width,
             dot_height: height,
             char_width: x_size,
             char_height: y_size,
-        }
+        })
     }
 
     /// maps x and y coordinates to which bit will represent the dot on the
@@ -121,7 +208,7 @@ impl BrailleImg {
 
     pub fn set_dot(&mut self, x: u32, y: u32, raised: bool) -> Result<(), Error> {
         if x > (self.dot_width - 1) || y > (self.dot_height - 1) {
-            return Err(Error::OutOfBounds(x, y, self.char_width, self.char_height));
+            return Err(Error::OutOfBounds(x, y, self.dot_width, self.dot_height));
         }
         let x_val_pos = x / 2;
         let y_val_pos = y / 4;
@@ -154,6 +241,65 @@ impl BrailleImg {
         Some(*val & mask != 0)
     }
 
+    /// the width in dots
+    pub fn width(&self) -> u32 {
+        self.dot_width
+    }
+
+    /// the height in dots
+    pub fn height(&self) -> u32 {
+        self.dot_height
+    }
+
+    /// the width in braille characters
+    pub fn char_width(&self) -> u32 {
+        self.char_width
+    }
+
+    /// the height in braille characters
+    pub fn char_height(&self) -> u32 {
+        self.char_height
+    }
+
+    /// the colors of the character at column `col` and row `row`, or [None] if
+    /// it's out of bounds or no colors were ever set on this image
+    pub fn cell_color(&self, col: u32, row: u32) -> Option<CellColor> {
+        if col >= self.char_width || row >= self.char_height {
+            return None;
+        }
+        self.colors
+            .as_ref()
+            .map(|c| c[(col + row * self.char_width) as usize])
+    }
+
+    /// sets the colors of the character at column `col` and row `row`
+    pub fn set_cell_color(&mut self, col: u32, row: u32, color: CellColor) -> Result<(), Error> {
+        if col >= self.char_width || row >= self.char_height {
+            return Err(Error::OutOfBounds(
+                col,
+                row,
+                self.char_width,
+                self.char_height,
+            ));
+        }
+        let len = self.braille_vals.len();
+        let colors = self
+            .colors
+            .get_or_insert_with(|| vec![CellColor::default(); len]);
+        colors[(col + row * self.char_width) as usize] = color;
+        Ok(())
+    }
+
+    /// whether any colors were set on this image
+    pub fn has_colors(&self) -> bool {
+        self.colors.is_some()
+    }
+
+    /// removes all colors from this image
+    pub fn clear_colors(&mut self) {
+        self.colors = None;
+    }
+
     /// # Arguments
     /// - `no_empty chars` if true, empty braille characters will be replaced by
     ///   another char with a single dot raised, which avoids skewing of rows of
@@ -177,64 +323,100 @@ impl BrailleImg {
             if i % self.char_width as usize == 0 && i != 0 {
                 braille_string.push(if break_line { '\n' } else { ' ' });
             }
-            if *val == 0 && no_empty_chars {
-                braille_string.push(BRAILLE_CHARS[1 << 2])
-            } else {
-                braille_string.push(BRAILLE_CHARS[*val as usize])
+            braille_string.push(Self::glyph(*val, no_empty_chars));
+        }
+        braille_string
+    }
+
+    /// same as [BrailleImg::as_str], but with ANSI escape codes setting the
+    /// colors of each character, if this image has any. colors are only
+    /// emitted when they differ from the previous character's, and are reset
+    /// at the end of every row
+    pub fn as_ansi(&self, no_empty_chars: bool, break_line: bool, mode: ColorMode) -> String {
+        let Some(colors) = &self.colors else {
+            return self.as_str(no_empty_chars, break_line);
+        };
+        let mut braille_string = String::with_capacity(self.str_len());
+        let mut current = (AnsiColor::Default, AnsiColor::Default);
+        for (i, (val, color)) in self.braille_vals.iter().zip(colors).enumerate() {
+            if i % self.char_width as usize == 0 && i != 0 {
+                if current != (AnsiColor::Default, AnsiColor::Default) {
+                    braille_string.push_str(ColorMode::RESET);
+                    current = (AnsiColor::Default, AnsiColor::Default);
+                }
+                braille_string.push(if break_line { '\n' } else { ' ' });
             }
+            let next = (
+                mode.quantize(color.foreground),
+                mode.quantize(color.background),
+            );
+            AnsiColor::write_transition(&mut braille_string, current, next);
+            current = next;
+            braille_string.push(Self::glyph(*val, no_empty_chars));
+        }
+        if current != (AnsiColor::Default, AnsiColor::Default) {
+            braille_string.push_str(ColorMode::RESET);
         }
         braille_string
     }
 
+    /// the character for a value of [BRAILLE_CHARS], where empty characters
+    /// are replaced by one with a single dot if `no_empty_chars` is true
+    fn glyph(val: u8, no_empty_chars: bool) -> char {
+        if val == 0 && no_empty_chars {
+            BRAILLE_CHARS[1 << 2]
+        } else {
+            BRAILLE_CHARS[val as usize]
+        }
+    }
+
     fn str_len(&self) -> usize {
         ((self.char_width * self.char_height) as usize * BRAILLE_LEN)
             + (self.char_height - 1) as usize
     }
 
+    /// creates a [Converter] for turning images into braille with more
+    /// options than [BrailleImg::from_image]
+    #[cfg(feature = "image")]
+    pub fn converter() -> Converter {
+        Converter::default()
+    }
+
+    /// converts an image to braille, where each pixel becomes a single dot,
+    /// using the default threshold, see [BrailleImg::converter] to pick
+    /// another one
+    /// # Arguments
+    /// - `ditherer` the dithering algorithm used to pick which dots are raised
+    /// - `invert` if false, dark pixels become raised dots, otherwise light
+    ///   pixels do
     #[cfg(feature = "image")]
     pub fn from_image(
         img: impl image::GenericImageView<Pixel = image::Rgba<u8>>,
         ditherer: impl Ditherer,
         invert: bool,
     ) -> Self {
-        let mut gray_img = image::GrayImage::new(img.width(), img.height());
-
-        let compute_lightness = |rgba: &[f32; 4]| -> u8 {
-            ((rgba[0] * 0.2126 + rgba[1] * 0.7152 + rgba[2] * 0.0722) * (rgba[3] / 255.0))
-                .clamp(0.0, 255.0)
-                .round() as u8
-        };
-
-        for (x, y, pix) in img.pixels() {
-            let lightness = compute_lightness(&[
-                pix.0[0] as f32,
-                pix.0[1] as f32,
-                pix.0[2] as f32,
-                pix.0[3] as f32,
-            ]);
-            gray_img.put_pixel(x, y, image::Luma::<u8>([lightness]));
-        }
-
-        ditherer.dither(&mut gray_img);
+        let raise = if invert { Raise::Light } else { Raise::Dark };
+        Self::converter()
+            .ditherer(ditherer)
+            .raise(raise)
+            .convert(img)
+    }
 
-        let mut braille_img = BrailleImg::new(gray_img.width(), gray_img.height());
-        // this is fine since the dimensions of gray_img are always the same as braille_img's
-        for (x, y, pix) in gray_img.enumerate_pixels() {
-            if invert {
-                if pix.0[0] > 96 {
-                    let _ = braille_img.set_dot(x, y, true);
-                }
-            } else if pix.0[0] < 96 {
-                let _ = braille_img.set_dot(x, y, true);
-            }
-        }
-        braille_img
+    /// converts an image to braille with a foreground and background color
+    /// for every character, picking the dots that best separate the two
+    /// colors of each one, see [Converter::convert_two_color]
+    #[cfg(feature = "image")]
+    pub fn from_image_two_color(
+        img: impl image::GenericImageView<Pixel = image::Rgba<u8>>,
+        raise: Raise,
+    ) -> Self {
+        Self::converter().raise(raise).convert_two_color(img)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::braille::BrailleImg;
+    use crate::braille::{BrailleImg, Error};
 
     #[test]
     fn str_len() {
@@ -252,6 +434,14 @@ mod tests {
         assert!(img.set_dot(31, 31, true).is_ok());
         assert!(img.set_dot(32, 31, true).is_err());
         assert!(img.set_dot(31, 32, true).is_err());
+        assert!(matches!(
+            img.set_dot(32, 0, true),
+            Err(Error::OutOfBounds(32, 0, 32, 32))
+        ));
+        assert!(matches!(
+            img.set_cell_color(16, 0, Default::default()),
+            Err(Error::OutOfBounds(16, 0, 16, 8))
+        ));
 
         assert!(img.get_dot(0, 0).is_some());
         assert!(img.get_dot(1, 1).is_some());
@@ -269,6 +459,40 @@ mod tests {
         assert_eq!(img.get_dot(0, 0), Some(true));
     }
 
+    #[test]
+    fn partial_rows() {
+        // the last row of characters is only partially covered by dots
+        let mut img = BrailleImg::new(2, 5);
+        assert!(img.set_dot(1, 4, true).is_ok());
+        assert_eq!(img.as_str(false, true), "⠀\n⠈");
+        // and there's no extra row when the height is a multiple of 4
+        assert_eq!(BrailleImg::new(2, 8).as_str(false, true), "⠀\n⠀");
+    }
+
+    #[test]
+    fn try_new() {
+        assert!(BrailleImg::try_new(3, 5).is_ok());
+        assert!(matches!(
+            BrailleImg::try_new(0, 5),
+            Err(Error::ZeroDimension(0, 5))
+        ));
+        assert!(matches!(
+            BrailleImg::try_new(u32::MAX, u32::MAX),
+            Err(Error::TooLarge(u32::MAX, u32::MAX))
+        ));
+        assert!(matches!(
+            BrailleImg::try_new(1 << 17, 1 << 18),
+            Err(Error::TooLarge(_, _))
+        ));
+
+        // sizes can be checked without allocating them
+        assert!(matches!(BrailleImg::check_dimensions(3, 5), Ok((2, 2))));
+        assert!(matches!(
+            BrailleImg::check_dimensions(1 << 17, 1 << 18),
+            Err(Error::TooLarge(_, _))
+        ));
+    }
+
     #[test]
     #[should_panic]
     fn new_null_width() {
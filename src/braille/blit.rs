@@ -0,0 +1,236 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+use super::BrailleImg;
+
+/// how the dots of a source image are combined with the dots of the image
+/// they are drawn onto by [BrailleImg::blit]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BlitOp {
+    /// raises dots raised in either image
+    #[default]
+    Or,
+    /// keeps dots raised in both images
+    And,
+    /// raises dots raised in exactly one of the images
+    Xor,
+    /// copies the dots of the source image
+    Replace,
+    /// lowers dots raised in the source image
+    AndNot,
+}
+
+impl BlitOp {
+    /// combines the dots of `dst` with the dots of `src`, where only the dots
+    /// in `mask` are changed
+    fn apply(self, dst: u8, src: u8, mask: u8) -> u8 {
+        let src = src & mask;
+        match self {
+            BlitOp::Or => dst | src,
+            BlitOp::And => dst & (src | !mask),
+            BlitOp::Xor => dst ^ src,
+            BlitOp::Replace => (dst & !mask) | src,
+            BlitOp::AndNot => dst & !src,
+        }
+    }
+}
+
+impl BrailleImg {
+    /// the bits of the character at `col` and `row` whose dots are inside of
+    /// the image, which are only missing in the last column and row
+    fn cell_mask(&self, col: u32, row: u32) -> u8 {
+        let mut mask = 0;
+        for y in row * 4..(row * 4 + 4).min(self.dot_height) {
+            for x in col * 2..(col * 2 + 2).min(self.dot_width) {
+                mask |= Self::get_bit_mask(x, y);
+            }
+        }
+        mask
+    }
+
+    /// combines the dots of `other` with the dots of this image using `op`,
+    /// with the top left of `other` at (`x`, `y`), which can be outside of
+    /// this image, where `other` is clipped silently. only dots covered by
+    /// `other` are changed, and colors are left as they are.
+    ///
+    /// if `x` is a multiple of 2 and `y` a multiple of 4, whole characters
+    /// are combined at once, which is much faster than going dot by dot
+    pub fn blit(&mut self, other: &BrailleImg, x: i32, y: i32, op: BlitOp) {
+        if x.rem_euclid(2) == 0 && y.rem_euclid(4) == 0 {
+            self.blit_cells(other, x.div_euclid(2) as i64, y.div_euclid(4) as i64, op);
+            return;
+        }
+
+        let (x, y) = (x as i64, y as i64);
+        let x0 = (-x).clamp(0, other.dot_width as i64) as u32;
+        let y0 = (-y).clamp(0, other.dot_height as i64) as u32;
+        let x1 = (self.dot_width as i64 - x).clamp(0, other.dot_width as i64) as u32;
+        let y1 = (self.dot_height as i64 - y).clamp(0, other.dot_height as i64) as u32;
+
+        for sy in y0..y1 {
+            for sx in x0..x1 {
+                let (dx, dy) = ((sx as i64 + x) as u32, (sy as i64 + y) as u32);
+                let src = other.get_dot(sx, sy) == Some(true);
+                let dst = self.get_dot(dx, dy) == Some(true);
+                let _ = self.set_dot(dx, dy, op.apply(dst as u8, src as u8, 1) != 0);
+            }
+        }
+    }
+
+    /// [BrailleImg::blit] for offsets in whole characters
+    fn blit_cells(&mut self, other: &BrailleImg, col: i64, row: i64, op: BlitOp) {
+        let c0 = (-col).clamp(0, other.char_width as i64) as u32;
+        let r0 = (-row).clamp(0, other.char_height as i64) as u32;
+        let c1 = (self.char_width as i64 - col).clamp(0, other.char_width as i64) as u32;
+        let r1 = (self.char_height as i64 - row).clamp(0, other.char_height as i64) as u32;
+
+        for sr in r0..r1 {
+            for sc in c0..c1 {
+                let (dc, dr) = ((sc as i64 + col) as u32, (sr as i64 + row) as u32);
+                let mask = other.cell_mask(sc, sr) & self.cell_mask(dc, dr);
+                let src = other.braille_vals[(sc + sr * other.char_width) as usize];
+                let dst = &mut self.braille_vals[(dc + dr * self.char_width) as usize];
+                *dst = op.apply(*dst, src, mask);
+            }
+        }
+    }
+
+    /// [BrailleImg::blit] for images of the same size
+    fn combine(&mut self, other: &BrailleImg, op: BlitOp) {
+        assert!(
+            self.dot_width == other.dot_width && self.dot_height == other.dot_height,
+            "both images must have the same size ({}x{} and {}x{})",
+            self.dot_width,
+            self.dot_height,
+            other.dot_width,
+            other.dot_height,
+        );
+        for (dst, src) in self.braille_vals.iter_mut().zip(&other.braille_vals) {
+            *dst = op.apply(*dst, *src, 0xff);
+        }
+    }
+}
+
+/// implements a binary operator between images of the same size, which keeps
+/// the colors of the left image
+macro_rules! impl_op {
+    ($op:ident, $fn:ident, $assign:ident, $assign_fn:ident, $blit:expr) => {
+        /// # Panics
+        /// if the images don't have the same size
+        impl $assign<&BrailleImg> for BrailleImg {
+            fn $assign_fn(&mut self, rhs: &BrailleImg) {
+                self.combine(rhs, $blit);
+            }
+        }
+
+        /// # Panics
+        /// if the images don't have the same size
+        impl $op<&BrailleImg> for BrailleImg {
+            type Output = BrailleImg;
+
+            fn $fn(mut self, rhs: &BrailleImg) -> BrailleImg {
+                self.combine(rhs, $blit);
+                self
+            }
+        }
+
+        /// # Panics
+        /// if the images don't have the same size
+        impl $op<&BrailleImg> for &BrailleImg {
+            type Output = BrailleImg;
+
+            fn $fn(self, rhs: &BrailleImg) -> BrailleImg {
+                self.clone().$fn(rhs)
+            }
+        }
+    };
+}
+
+impl_op!(BitOr, bitor, BitOrAssign, bitor_assign, BlitOp::Or);
+impl_op!(BitAnd, bitand, BitAndAssign, bitand_assign, BlitOp::And);
+impl_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, BlitOp::Xor);
+
+/// inverts every dot, keeping the colors
+impl Not for BrailleImg {
+    type Output = BrailleImg;
+
+    fn not(mut self) -> BrailleImg {
+        for row in 0..self.char_height {
+            for col in 0..self.char_width {
+                let mask = self.cell_mask(col, row);
+                let val = &mut self.braille_vals[(col + row * self.char_width) as usize];
+                *val = !*val & mask;
+            }
+        }
+        self
+    }
+}
+
+/// inverts every dot, keeping the colors
+impl Not for &BrailleImg {
+    type Output = BrailleImg;
+
+    fn not(self) -> BrailleImg {
+        !self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blits() {
+        let sprite: BrailleImg = "⣿⡇".parse().unwrap();
+        for (x, y, expected) in [
+            (0, 0, "⣿⡇⠀\n⠀⠀⠀"),
+            (2, 4, "⠀⠀⠀\n⠀⣿⡇"),
+            (-2, 4, "⠀⠀⠀\n⡇⠀⠀"),
+            (1, 2, "⢠⣤⠀\n⠘⠛⠀"),
+            (-1, -3, "⠉⠀⠀\n⠀⠀⠀"),
+            (7, 0, "⠀⠀⠀\n⠀⠀⠀"),
+        ] {
+            let mut img = BrailleImg::new(6, 8);
+            img.blit(&sprite, x, y, BlitOp::Or);
+            assert_eq!(img.as_str(false, true), expected, "at ({x}, {y})");
+        }
+
+        let mut img: BrailleImg = "⠉⣀⣿".parse().unwrap();
+        let dots: BrailleImg = "⠛".parse().unwrap();
+        for (x, y, op, expected) in [
+            (0, 0, BlitOp::And, "⠉⣀⣿"),
+            (2, 0, BlitOp::And, "⠉⠀⣿"),
+            (2, 0, BlitOp::Xor, "⠉⠛⣿"),
+            (4, 0, BlitOp::Replace, "⠉⠛⠛"),
+            (1, 0, BlitOp::AndNot, "⠁⠘⠛"),
+            (3, 1, BlitOp::Replace, "⠁⠸⠟"),
+        ] {
+            img.blit(&dots, x, y, op);
+            assert_eq!(img.as_str(false, true), expected, "{op:?} at ({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn operators() {
+        let a: BrailleImg = "⣿⠀⠉".parse().unwrap();
+        let b: BrailleImg = "⠛⠛⠛".parse().unwrap();
+        assert_eq!((&a | &b).as_str(false, true), "⣿⠛⠛");
+        assert_eq!((&a & &b).as_str(false, true), "⠛⠀⠉");
+        assert_eq!((&a ^ &b).as_str(false, true), "⣤⠛⠒");
+        assert_eq!((!&a).as_str(false, true), "⠀⣿⣶");
+
+        let mut c = a;
+        c |= &b;
+        assert_eq!(c, "⣿⠛⠛".parse().unwrap());
+
+        // dots outside of the image stay lowered
+        let img = !BrailleImg::new(3, 3);
+        assert_eq!(img.as_str(false, true), "⠿⠇");
+        assert_eq!((!img).as_str(false, true), "⠀⠀");
+    }
+
+    #[test]
+    #[should_panic]
+    fn operators_size_mismatch() {
+        let _ = BrailleImg::new(2, 4) | &BrailleImg::new(4, 4);
+    }
+}
@@ -0,0 +1,372 @@
+use super::BrailleImg;
+
+/// drawing primitives, which take signed coordinates in dots so shapes can
+/// be partially outside of the image, where they are clipped silently. every
+/// method sets the dots it draws to `raised`, so shapes can be erased too
+impl BrailleImg {
+    /// sets a dot, ignoring coordinates outside of the image
+    fn plot(&mut self, x: i64, y: i64, raised: bool) {
+        if let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) {
+            let _ = self.set_dot(x, y, raised);
+        }
+    }
+
+    /// sets a horizontal run of dots from `x0` to `x1`, both inclusive
+    fn span(&mut self, x0: i64, x1: i64, y: i64, raised: bool) {
+        if y < 0 || y >= self.dot_height as i64 {
+            return;
+        }
+        let start = x0.min(x1).max(0);
+        let end = x0.max(x1).min(self.dot_width as i64 - 1);
+        for x in start..=end {
+            self.plot(x, y, raised);
+        }
+    }
+
+    /// draws a line from (`x0`, `y0`) to (`x1`, `y1`), both inclusive, where
+    /// each step along the longer axis sets the dot closest to the line. only
+    /// the part of the line inside of the image is walked
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, raised: bool) {
+        let (x0, y0, x1, y1) = (x0 as i64, y0 as i64, x1 as i64, y1 as i64);
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        // walks along the longer axis as `a`, from the lower end, so a line
+        // looks the same in both directions
+        let (mut a0, mut b0, mut a1, mut b1) = if steep {
+            (y0, x0, y1, x1)
+        } else {
+            (x0, y0, x1, y1)
+        };
+        if a0 > a1 {
+            (a0, b0, a1, b1) = (a1, b1, a0, b0);
+        }
+        let (da, db) = (a1 - a0, b1 - b0);
+        let size = if steep {
+            self.dot_height
+        } else {
+            self.dot_width
+        } as i64;
+
+        for a in a0.max(0)..=a1.min(size - 1) {
+            let b = if da == 0 {
+                b0
+            } else {
+                // rounds to the closest dot, with halves rounded up, where the
+                // product of both lengths only fits in 128 bits
+                let (t, da, db) = ((a - a0) as i128, da as i128, db as i128);
+                b0 + (2 * t * db + da).div_euclid(2 * da) as i64
+            };
+            if steep {
+                self.plot(b, a, raised);
+            } else {
+                self.plot(a, b, raised);
+            }
+        }
+    }
+
+    /// draws lines connecting each point to the next one
+    pub fn draw_polyline(&mut self, points: &[(i32, i32)], raised: bool) {
+        for pair in points.windows(2) {
+            let [(x0, y0), (x1, y1)] = [pair[0], pair[1]];
+            self.draw_line(x0, y0, x1, y1, raised);
+        }
+    }
+
+    /// draws a rectangle with its top left corner at (`x`, `y`), either only
+    /// its outline or `filled`
+    pub fn draw_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        filled: bool,
+        raised: bool,
+    ) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let (x0, y0) = (x as i64, y as i64);
+        let (x1, y1) = (x0 + width as i64 - 1, y0 + height as i64 - 1);
+
+        for y in y0.max(0)..=y1.min(self.dot_height as i64 - 1) {
+            if filled || y == y0 || y == y1 {
+                self.span(x0, x1, y, raised);
+            } else {
+                self.plot(x0, y, raised);
+                self.plot(x1, y, raised);
+            }
+        }
+    }
+
+    /// draws a circle around (`cx`, `cy`), either only its outline or `filled`
+    pub fn draw_circle(&mut self, cx: i32, cy: i32, radius: u32, filled: bool, raised: bool) {
+        self.draw_ellipse(cx, cy, radius, radius, filled, raised);
+    }
+
+    /// draws an ellipse around (`cx`, `cy`) with the horizontal radius `rx`
+    /// and the vertical radius `ry`, either only its outline or `filled`.
+    /// the outline sets the dot closest to the ellipse in every row and every
+    /// column it crosses, and only rows and columns inside of the image are
+    /// visited, so huge ellipses are as fast as small ones. radii larger than
+    /// [i32::MAX] are drawn as [i32::MAX], which is still far larger than any
+    /// image
+    pub fn draw_ellipse(&mut self, cx: i32, cy: i32, rx: u32, ry: u32, filled: bool, raised: bool) {
+        let (cx, cy) = (cx as i64, cy as i64);
+        let (rx, ry) = (
+            rx.min(i32::MAX as u32) as i64,
+            ry.min(i32::MAX as u32) as i64,
+        );
+        let rows = (cy - ry).max(0)..=(cy + ry).min(self.dot_height as i64 - 1);
+        let columns = (cx - rx).max(0)..=(cx + rx).min(self.dot_width as i64 - 1);
+        if rows.is_empty() || columns.is_empty() {
+            return;
+        }
+        if rx == 0 || ry == 0 {
+            for y in rows {
+                self.span(cx - rx, cx + rx, y, raised);
+            }
+            return;
+        }
+
+        // the squares of the radii and of the offsets from the center are at
+        // most 2^62, so their products only fit in 128 bits
+        let (a, b) = (rx as u128, ry as u128);
+        // the offset along one axis of the dot closest to the ellipse at
+        // `offset` along the other axis, where `r` is the radius along that
+        // other axis and `q` along the returned one
+        let closest = |offset: i64, r: u128, q: u128| {
+            let offset = offset.unsigned_abs() as u128;
+            let root = (4 * q * q * (r * r - offset * offset)).isqrt();
+            ((root + r) / (2 * r)) as i64
+        };
+
+        if filled {
+            for y in rows {
+                // the spans also reach every column whose closest dot is in
+                // this row or further from the center
+                let dy = (y - cy).unsigned_abs() as u128;
+                let half = (2 * dy).saturating_sub(1);
+                let columns = (a * a * (4 * b * b - half * half)).isqrt() / (2 * b);
+                let x = closest(y - cy, b, a).max(columns as i64);
+                self.span(cx - x, cx + x, y, raised);
+            }
+        } else {
+            for y in rows {
+                let x = closest(y - cy, b, a);
+                self.plot(cx - x, y, raised);
+                self.plot(cx + x, y, raised);
+            }
+            for x in columns {
+                let y = closest(x - cx, a, b);
+                self.plot(x, cy - y, raised);
+                self.plot(x, cy + y, raised);
+            }
+        }
+    }
+
+    /// fills the polygon with `points` as its corners, including its outline,
+    /// where overlapping parts are filled using the even-odd rule
+    pub fn fill_polygon(&mut self, points: &[(i32, i32)], raised: bool) {
+        let Some(&first) = points.first() else {
+            return;
+        };
+        let edges: Vec<_> = points
+            .iter()
+            .zip(points.iter().skip(1).chain([&first]))
+            .map(|(&(x0, y0), &(x1, y1))| (x0 as i64, y0 as i64, x1 as i64, y1 as i64))
+            .collect();
+
+        let min_y = points.iter().map(|p| p.1 as i64).min().unwrap_or(0).max(0);
+        let max_y = points
+            .iter()
+            .map(|p| p.1 as i64)
+            .max()
+            .unwrap_or(0)
+            .min(self.dot_height as i64 - 1);
+
+        let mut crossings = Vec::new();
+        for y in min_y..=max_y {
+            crossings.clear();
+            for &(x0, y0, x1, y1) in &edges {
+                // half open so vertices shared by two edges only count once
+                if (y0 <= y && y < y1) || (y1 <= y && y < y0) {
+                    crossings
+                        .push(x0 as f64 + (y - y0) as f64 * (x1 - x0) as f64 / (y1 - y0) as f64);
+                }
+            }
+            crossings.sort_by(f64::total_cmp);
+            for pair in crossings.chunks_exact(2) {
+                self.span(pair[0].ceil() as i64, pair[1].floor() as i64, y, raised);
+            }
+        }
+
+        self.draw_polyline(points, raised);
+        let last = points[points.len() - 1];
+        self.draw_line(last.0, last.1, first.0, first.1, raised);
+    }
+
+    /// sets every dot connected to (`x`, `y`) through dots in the same state
+    /// as it, not counting diagonals, to `raised`
+    pub fn flood_fill(&mut self, x: i32, y: i32, raised: bool) {
+        let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) else {
+            return;
+        };
+        let Some(target) = self.get_dot(x, y) else {
+            return;
+        };
+        if target == raised {
+            return;
+        }
+
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            if self.get_dot(x, y) != Some(target) {
+                continue;
+            }
+            let _ = self.set_dot(x, y, raised);
+            if x > 0 {
+                stack.push((x - 1, y));
+            }
+            if y > 0 {
+                stack.push((x, y - 1));
+            }
+            stack.push((x + 1, y));
+            stack.push((x, y + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raised(img: &BrailleImg) -> Vec<(u32, u32)> {
+        (0..img.height())
+            .flat_map(|y| (0..img.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| img.get_dot(x, y) == Some(true))
+            .collect()
+    }
+
+    #[test]
+    fn lines() {
+        let mut img = BrailleImg::new(8, 8);
+        img.draw_line(0, 0, 7, 7, true);
+        assert_eq!(raised(&img), (0..8).map(|i| (i, i)).collect::<Vec<_>>());
+
+        let mut img = BrailleImg::new(8, 8);
+        img.draw_line(-10, 3, 20, 3, true);
+        img.draw_line(2, 100, 2, -100, true);
+        assert_eq!(raised(&img).len(), 15);
+
+        let mut img = BrailleImg::new(8, 8);
+        img.draw_polyline(&[(0, 0), (4, 0), (4, 4)], true);
+        assert_eq!(raised(&img).len(), 9);
+        img.draw_line(0, 0, 4, 0, false);
+        assert_eq!(raised(&img).len(), 4);
+
+        // lines look the same in both directions, and only their visible part
+        // is walked
+        let (mut a, mut b) = (BrailleImg::new(8, 8), BrailleImg::new(8, 8));
+        a.draw_line(0, 1, 7, 4, true);
+        b.draw_line(7, 4, 0, 1, true);
+        assert_eq!(a, b);
+        let mut img = BrailleImg::new(8, 8);
+        img.draw_line(i32::MIN, i32::MIN, i32::MAX, i32::MAX, true);
+        img.draw_line(0, 7, i32::MAX, 7, true);
+        assert_eq!(raised(&img).len(), 15);
+    }
+
+    #[test]
+    fn rects() {
+        let mut img = BrailleImg::new(8, 8);
+        img.draw_rect(1, 1, 4, 3, false, true);
+        assert_eq!(raised(&img).len(), 10);
+        img.draw_rect(1, 1, 4, 3, true, true);
+        assert_eq!(raised(&img).len(), 12);
+        img.draw_rect(-2, -2, 100, 100, true, true);
+        assert_eq!(raised(&img).len(), 64);
+    }
+
+    #[test]
+    fn circles() {
+        let mut img = BrailleImg::new(16, 16);
+        img.draw_circle(7, 7, 5, false, true);
+        let outline = raised(&img);
+        for (x, y) in [(2, 7), (12, 7), (7, 2), (7, 12)] {
+            assert!(outline.contains(&(x, y)));
+        }
+        // the outline is symmetric and has no gaps
+        for &(x, y) in &outline {
+            assert!(outline.contains(&(14 - x, y)) && outline.contains(&(y, x)));
+            let neighbours = outline
+                .iter()
+                .filter(|&&(nx, ny)| nx.abs_diff(x) <= 1 && ny.abs_diff(y) <= 1)
+                .count();
+            assert!(neighbours >= 3);
+        }
+
+        img.draw_circle(7, 7, 5, true, true);
+        assert_eq!(img.get_dot(7, 7), Some(true));
+        assert_eq!(img.get_dot(4, 4), Some(true));
+        assert_eq!(img.get_dot(3, 3), Some(false));
+
+        let mut img = BrailleImg::new(16, 8);
+        img.draw_ellipse(7, 3, 6, 2, false, true);
+        assert!(raised(&img)
+            .iter()
+            .all(|&(x, y)| (1..=13).contains(&x) && (1..=5).contains(&y)));
+        img.draw_circle(-3, -3, 40, true, true);
+        assert_eq!(raised(&img).len(), 128);
+
+        // huge shapes are clipped like any other
+        let mut img = BrailleImg::new(8, 8);
+        img.draw_circle(0, 0, 60000, false, true);
+        assert_eq!(raised(&img).len(), 0);
+        img.draw_ellipse(0, 60000, 60000, 60000, false, true);
+        assert_eq!(raised(&img), (0..8).map(|x| (x, 0)).collect::<Vec<_>>());
+        img.draw_rect(i32::MIN, 3, u32::MAX, 1, false, true);
+        assert_eq!(raised(&img).len(), 16);
+        img.draw_rect(7, i32::MIN, 1, u32::MAX, false, true);
+        assert_eq!(raised(&img).len(), 22);
+
+        // only the rows and columns inside of the image are visited, so these
+        // finish right away
+        let mut img = BrailleImg::new(8, 8);
+        img.draw_circle(4, 100_000_002, 100_000_000, false, true);
+        assert_eq!(raised(&img), (0..8).map(|x| (x, 2)).collect::<Vec<_>>());
+        img.draw_circle(4, 100_000_002, 100_000_000, true, true);
+        assert_eq!(raised(&img).len(), 48);
+        let mut img = BrailleImg::new(8, 8);
+        img.draw_ellipse(4, 4, 100_000_000, 3, true, true);
+        assert_eq!(raised(&img).len(), 56);
+        img.draw_circle(0, 0, u32::MAX, false, true);
+        assert_eq!(raised(&img).len(), 56);
+    }
+
+    #[test]
+    fn polygons() {
+        let mut img = BrailleImg::new(8, 8);
+        img.fill_polygon(&[(0, 0), (6, 0), (0, 6)], true);
+        // a right triangle with both legs 7 dots long
+        assert_eq!(raised(&img).len(), 28);
+        assert_eq!(img.get_dot(3, 3), Some(true));
+        assert_eq!(img.get_dot(4, 3), Some(false));
+
+        let mut img = BrailleImg::new(8, 8);
+        img.fill_polygon(&[(-4, -4), (20, -4), (20, 20), (-4, 20)], true);
+        assert_eq!(raised(&img).len(), 64);
+    }
+
+    #[test]
+    fn flood_fills() {
+        let mut img = BrailleImg::new(8, 8);
+        img.draw_rect(0, 0, 5, 5, false, true);
+        img.flood_fill(2, 2, true);
+        assert_eq!(raised(&img).len(), 25);
+        img.flood_fill(7, 7, true);
+        assert_eq!(raised(&img).len(), 64);
+        img.flood_fill(-1, 0, false);
+        img.flood_fill(0, 0, false);
+        assert_eq!(raised(&img).len(), 0);
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt::Write;
+
+use super::BrailleImg;
+use crate::color::CellColor;
+
+impl BrailleImg {
+    /// the style of the `<pre>` element, which keeps rows of characters from
+    /// having gaps between them
+    const HTML_STYLE: &'static str = "font-family: 'DejaVu Sans Mono', Menlo, Consolas, monospace; \
+        line-height: 1; letter-spacing: 0; font-variant-ligatures: none; white-space: pre; margin: 0";
+
+    /// renders this image as a self-contained HTML `<pre>` element, where
+    /// each run of characters with the same colors is wrapped in a `<span>`
+    /// if this image has any colors.
+    ///
+    /// only braille characters and newlines are ever written as text, so
+    /// nothing needs escaping
+    /// # Arguments
+    /// - `no_empty chars` if true, empty braille characters will be replaced by
+    ///   another char with a single dot raised, which avoids skewing of rows of
+    ///   characters
+    pub fn as_html(&self, no_empty_chars: bool) -> String {
+        let mut html = String::with_capacity(self.str_len() + 256);
+        let _ = write!(html, "<pre style=\"{}\">", Self::HTML_STYLE);
+
+        for row in 0..self.char_height {
+            if row != 0 {
+                html.push('\n');
+            }
+            let mut current: Option<CellColor> = None;
+            for col in 0..self.char_width {
+                let idx = (col + row * self.char_width) as usize;
+                let color = self
+                    .colors
+                    .as_ref()
+                    .map(|c| c[idx])
+                    .filter(|c| *c != CellColor::default());
+                if color != current {
+                    if current.is_some() {
+                        html.push_str("</span>");
+                    }
+                    if let Some(color) = color {
+                        write_span(&mut html, color);
+                    }
+                    current = color;
+                }
+                html.push(Self::glyph(self.braille_vals[idx], no_empty_chars));
+            }
+            if current.is_some() {
+                html.push_str("</span>");
+            }
+        }
+
+        html.push_str("</pre>");
+        html
+    }
+}
+
+fn write_span(html: &mut String, color: CellColor) {
+    html.push_str("<span style=\"");
+    if let Some(fg) = color.foreground {
+        let _ = write!(html, "color: #{fg:x};");
+    }
+    if let Some(bg) = color.background {
+        let _ = write!(html, "background-color: #{bg:x};");
+    }
+    html.push_str("\">");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Rgb;
+
+    #[test]
+    fn html_spans() {
+        let mut img = BrailleImg::new(6, 8);
+        assert!(!img.as_html(false).contains("<span"));
+        assert!(img.as_html(false).ends_with("⠀⠀⠀\n⠀⠀⠀</pre>"));
+
+        let red = CellColor {
+            foreground: Some(Rgb::new(255, 0, 0)),
+            background: None,
+        };
+        let blue = CellColor {
+            foreground: Some(Rgb::new(0, 0, 255)),
+            background: Some(Rgb::new(1, 2, 3)),
+        };
+        img.set_cell_color(0, 0, red).unwrap();
+        img.set_cell_color(1, 0, red).unwrap();
+        img.set_cell_color(2, 0, blue).unwrap();
+        img.set_dot(0, 0, true).unwrap();
+
+        let html = img.as_html(true);
+        let body = html.split_once('>').unwrap().1;
+        assert_eq!(
+            body,
+            "<span style=\"color: #ff0000;\">⠁⠄</span>\
+             <span style=\"color: #0000ff;background-color: #010203;\">⠄</span>\n\
+             ⠄⠄⠄</pre>"
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use std::str::FromStr;
+
+use super::{BrailleImg, Error, BRAILLE_CHARS};
+
+impl BrailleImg {
+    /// parses braille characters, with rows separated by newlines or spaces
+    /// like the output of [BrailleImg::as_str], into a [BrailleImg] that is
+    /// as wide as the longest row, where shorter rows are filled with empty
+    /// characters. repeated or trailing whitespace within a line doesn't start
+    /// a new row, but an empty line is an empty row, and empty rows at the end
+    /// are ignored
+    /// # Arguments
+    /// - `skip_invalid` if true, characters that aren't braille are skipped,
+    ///   otherwise they are an [Error::InvalidChar]
+    /// # Errors
+    /// - [Error::InvalidChar] if `skip_invalid` is false and a character other
+    ///   than braille or a separator is found
+    /// - [Error::Empty] if there are no braille characters at all
+    pub fn parse(s: &str, skip_invalid: bool) -> Result<Self, Error> {
+        let mut rows: Vec<Vec<u8>> = Vec::new();
+        for row in s.lines().flat_map(rows_of_line) {
+            let index = rows.len() as u32;
+            let mut vals = Vec::new();
+            for (col, c) in row.chars().enumerate() {
+                match braille_value(c) {
+                    Some(val) => vals.push(val),
+                    None if skip_invalid => {}
+                    None => return Err(Error::InvalidChar(c, col as u32, index)),
+                }
+            }
+            rows.push(vals);
+        }
+        while rows.last().is_some_and(Vec::is_empty) {
+            rows.pop();
+        }
+
+        let char_width = rows.iter().map(Vec::len).max().unwrap_or(0) as u32;
+        if char_width == 0 {
+            return Err(Error::Empty);
+        }
+        let mut img = BrailleImg::new(char_width * 2, rows.len() as u32 * 4);
+        for (row, vals) in rows.iter().enumerate() {
+            let start = row * char_width as usize;
+            img.braille_vals[start..start + vals.len()].copy_from_slice(vals);
+        }
+        Ok(img)
+    }
+}
+
+/// parses braille characters like [BrailleImg::parse], returning an error for
+/// any character that isn't braille
+impl FromStr for BrailleImg {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, false)
+    }
+}
+
+/// the rows in a line, separated by whitespace, where a line without any
+/// characters other than whitespace is a single empty row
+fn rows_of_line(line: &str) -> impl Iterator<Item = &str> {
+    let mut rows = line.split(char::is_whitespace).filter(|s| !s.is_empty());
+    let first = rows.next().unwrap_or_default();
+    std::iter::once(first).chain(rows)
+}
+
+/// the value of a braille character according to [BRAILLE_CHARS]
+fn braille_value(c: char) -> Option<u8> {
+    let val = (c as u32).checked_sub(BRAILLE_CHARS[0] as u32)?;
+    u8::try_from(val).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_round_trip() {
+        let mut img = BrailleImg::new(6, 8);
+        for (x, y) in [(0, 0), (1, 3), (5, 4), (2, 7)] {
+            img.set_dot(x, y, true).unwrap();
+        }
+        for break_line in [true, false] {
+            let parsed: BrailleImg = img.as_str(false, break_line).parse().unwrap();
+            assert_eq!(parsed.as_str(false, true), img.as_str(false, true));
+        }
+
+        assert!(BRAILLE_CHARS
+            .iter()
+            .enumerate()
+            .all(|(i, c)| braille_value(*c) == Some(i as u8)));
+    }
+
+    #[test]
+    fn parse_ragged() {
+        let img = BrailleImg::parse("⣿\r\n\n⠁⠂⠄\n\n", false).unwrap();
+        assert_eq!((img.width(), img.height()), (6, 12));
+        assert_eq!(img.as_str(false, true), "⣿⠀⠀\n⠀⠀⠀\n⠁⠂⠄");
+
+        // whitespace pasted around rows doesn't add any
+        let img = BrailleImg::parse("⣿⣿ \n⣿⣿\t\n", false).unwrap();
+        assert_eq!((img.width(), img.height()), (4, 8));
+        let img = BrailleImg::parse("  ⣿⣿  ⠁\n \n⣿", false).unwrap();
+        assert_eq!(img.as_str(false, true), "⣿⣿\n⠁⠀\n⠀⠀\n⣿⠀");
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(matches!(
+            "⣿⣿\n⣿a".parse::<BrailleImg>(),
+            Err(Error::InvalidChar('a', 1, 1))
+        ));
+        let img = BrailleImg::parse("art\n⣿a⣿", true).unwrap();
+        assert_eq!(img.as_str(false, true), "⠀⠀\n⣿⣿");
+
+        assert!(matches!(BrailleImg::parse("", false), Err(Error::Empty)));
+        assert!(matches!(
+            BrailleImg::parse("abc\n", true),
+            Err(Error::Empty)
+        ));
+    }
+}
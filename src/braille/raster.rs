@@ -0,0 +1,184 @@
+use image::{GrayImage, Luma, Rgba, RgbaImage};
+
+use super::BrailleImg;
+use crate::color::Rgb;
+
+/// options for drawing a [BrailleImg] like it would look in a terminal with
+/// [BrailleImg::render]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderOptions {
+    cell_width: u32,
+    cell_height: u32,
+    dot_size: f32,
+    foreground: Rgb,
+    background: Option<Rgb>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            cell_width: 8,
+            cell_height: 16,
+            dot_size: 0.7,
+            foreground: Rgb::new(255, 255, 255),
+            background: Some(Rgb::new(0, 0, 0)),
+        }
+    }
+}
+
+impl RenderOptions {
+    /// size in pixels of each character, defaults to 8x16
+    /// # Panics
+    /// if either `width` or `height` are 0
+    pub fn cell_size(mut self, width: u32, height: u32) -> Self {
+        assert!(width != 0, "cell width must be greater than 0");
+        assert!(height != 0, "cell height must be greater than 0");
+        self.cell_width = width;
+        self.cell_height = height;
+        self
+    }
+
+    /// diameter of each dot relative to the distance between dots, defaults
+    /// to 0.7
+    pub fn dot_size(mut self, size: f32) -> Self {
+        self.dot_size = size;
+        self
+    }
+
+    /// color of raised dots in characters without a foreground color,
+    /// defaults to white
+    pub fn foreground(mut self, color: Rgb) -> Self {
+        self.foreground = color;
+        self
+    }
+
+    /// color behind the dots of characters without a background color,
+    /// defaults to black, where none leaves it transparent
+    pub fn background(mut self, color: Option<Rgb>) -> Self {
+        self.background = color;
+        self
+    }
+}
+
+impl BrailleImg {
+    /// turns this image into a grayscale image with one pixel per dot, where
+    /// raised dots are white and all others are black
+    pub fn to_image(&self) -> GrayImage {
+        GrayImage::from_fn(self.dot_width, self.dot_height, |x, y| {
+            Luma([if self.get_dot(x, y) == Some(true) {
+                255
+            } else {
+                0
+            }])
+        })
+    }
+
+    /// turns this image into an image with one pixel per dot, where raised
+    /// dots get the foreground color of their character and all others get
+    /// its background color.
+    ///
+    /// characters without colors have white raised dots and transparent
+    /// unraised ones
+    pub fn to_rgba_image(&self) -> RgbaImage {
+        RgbaImage::from_fn(self.dot_width, self.dot_height, |x, y| {
+            let color = self.cell_color(x / 2, y / 4).unwrap_or_default();
+            let raised = self.get_dot(x, y) == Some(true);
+            match (raised, color.foreground, color.background) {
+                (true, Some(fg), _) => opaque(fg),
+                (true, None, _) => Rgba([255; 4]),
+                (false, _, Some(bg)) => opaque(bg),
+                (false, _, None) => Rgba([0; 4]),
+            }
+        })
+    }
+
+    /// draws this image with round, anti-aliased dots laid out like braille
+    /// characters in a terminal, which is useful for previews where unicode
+    /// can't be displayed reliably.
+    ///
+    /// the colors of characters are used if this image has any, otherwise
+    /// the colors of `options`
+    pub fn render(&self, options: &RenderOptions) -> RgbaImage {
+        let (cw, ch) = (options.cell_width, options.cell_height);
+        let pitch_x = cw as f32 / 2.0;
+        let pitch_y = ch as f32 / 4.0;
+        let radius = pitch_x.min(pitch_y) * options.dot_size / 2.0;
+
+        RgbaImage::from_fn(self.char_width * cw, self.char_height * ch, |px, py| {
+            let (col, row) = (px / cw, py / ch);
+            let color = self.cell_color(col, row).unwrap_or_default();
+            let fg = color.foreground.unwrap_or(options.foreground);
+            let bg = color.background.or(options.background);
+
+            // the nearest dot center is the only one that can cover this pixel
+            let cx = px as f32 + 0.5 - (col * cw) as f32;
+            let cy = py as f32 + 0.5 - (row * ch) as f32;
+            let dx = ((cx / pitch_x) as u32).min(1);
+            let dy = ((cy / pitch_y) as u32).min(3);
+            let coverage = if self.get_dot(col * 2 + dx, row * 4 + dy) == Some(true) {
+                let distance =
+                    (cx - (dx as f32 + 0.5) * pitch_x).hypot(cy - (dy as f32 + 0.5) * pitch_y);
+                (radius + 0.5 - distance).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            match bg {
+                Some(bg) => {
+                    let mix =
+                        |f: u8, b: u8| (b as f32 + (f as f32 - b as f32) * coverage).round() as u8;
+                    Rgba([mix(fg.r, bg.r), mix(fg.g, bg.g), mix(fg.b, bg.b), 255])
+                }
+                None => Rgba([fg.r, fg.g, fg.b, (coverage * 255.0).round() as u8]),
+            }
+        })
+    }
+}
+
+fn opaque(color: Rgb) -> Rgba<u8> {
+    Rgba([color.r, color.g, color.b, 255])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::CellColor;
+
+    #[test]
+    fn rasterize() {
+        let mut img = BrailleImg::new(3, 5);
+        img.set_dot(0, 0, true).unwrap();
+        img.set_dot(2, 4, true).unwrap();
+
+        let gray = img.to_image();
+        assert_eq!(gray.dimensions(), (3, 5));
+        assert_eq!(gray.get_pixel(0, 0).0, [255]);
+        assert_eq!(gray.get_pixel(1, 0).0, [0]);
+        assert_eq!(gray.get_pixel(2, 4).0, [255]);
+
+        let red = Rgb::new(255, 0, 0);
+        img.set_cell_color(
+            1,
+            1,
+            CellColor {
+                foreground: Some(red),
+                background: Some(Rgb::new(0, 0, 255)),
+            },
+        )
+        .unwrap();
+        let rgba = img.to_rgba_image();
+        assert_eq!(rgba.get_pixel(0, 0).0, [255; 4]);
+        assert_eq!(rgba.get_pixel(1, 0).0, [0; 4]);
+        assert_eq!(rgba.get_pixel(2, 4).0, [255, 0, 0, 255]);
+
+        let options = RenderOptions::default().cell_size(10, 20).dot_size(0.6);
+        let rendered = img.render(&options);
+        assert_eq!(rendered.dimensions(), (20, 40));
+        // centers of a raised and an unraised dot, and a gap between dots
+        assert_eq!(rendered.get_pixel(2, 2).0, [255; 4]);
+        assert_eq!(rendered.get_pixel(7, 2).0, [0, 0, 0, 255]);
+        assert_eq!(rendered.get_pixel(4, 2).0, [0, 0, 0, 255]);
+        assert_eq!(rendered.get_pixel(12, 22).0, [255, 0, 0, 255]);
+        assert_eq!(rendered.get_pixel(17, 22).0, [0, 0, 255, 255]);
+    }
+}
@@ -0,0 +1,165 @@
+#[cfg(feature = "image")]
+use image::{GrayImage, Luma};
+
+use super::{BrailleImg, Error};
+#[cfg(feature = "image")]
+use crate::dithering::{DiffusionKernel, Ditherer, ErrorDiffusion};
+
+/// how [BrailleImg::resize] picks the dots of the resized image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum ResizeMethod {
+    /// each dot takes the state of the dot closest to its center, which keeps
+    /// lines and text sharp, but drops or doubles rows and columns of dots
+    #[default]
+    Nearest,
+    /// each dot is covered by how much of its area is raised in this image,
+    /// which is then dithered again with an [ErrorDiffusion] using
+    /// [DiffusionKernel::FLOYD_STEINBERG], so shaded images keep their density
+    /// of dots
+    #[cfg(feature = "image")]
+    Area,
+}
+
+impl BrailleImg {
+    /// resamples the dots of this image into a new image that is `width` by
+    /// `height` dots, which is useful when the original image is gone and only
+    /// its braille is left. colors are taken from the character each
+    /// character's middle dot comes from, like with the geometric transforms
+    /// # Errors
+    /// the same as [BrailleImg::try_new]
+    pub fn resize(&self, width: u32, height: u32, method: ResizeMethod) -> Result<Self, Error> {
+        BrailleImg::check_dimensions(width, height)?;
+        let scale_x = self.dot_width as f64 / width as f64;
+        let scale_y = self.dot_height as f64 / height as f64;
+        let nearest = |x: u32, y: u32| {
+            let sx = ((x as f64 + 0.5) * scale_x) as u32;
+            let sy = ((y as f64 + 0.5) * scale_y) as u32;
+            Some((sx.min(self.dot_width - 1), sy.min(self.dot_height - 1)))
+        };
+
+        Ok(match method {
+            ResizeMethod::Nearest => self.remap(width, height, nearest),
+            #[cfg(feature = "image")]
+            ResizeMethod::Area => {
+                let mut buffer = GrayImage::from_fn(width, height, |x, y| {
+                    let area = self.area(
+                        (x as f64 * scale_x, (x + 1) as f64 * scale_x),
+                        (y as f64 * scale_y, (y + 1) as f64 * scale_y),
+                    );
+                    Luma([(area * 255.0).round() as u8])
+                });
+                // dots that are at least half covered are raised
+                ErrorDiffusion::new(DiffusionKernel::FLOYD_STEINBERG).dither(&mut buffer, 127);
+
+                let mut img = BrailleImg::new(width, height);
+                for (x, y, pix) in buffer.enumerate_pixels() {
+                    if pix.0[0] == 255 {
+                        let _ = img.set_dot(x, y, true);
+                    }
+                }
+                self.remap_colors(&mut img, nearest);
+                img
+            }
+        })
+    }
+
+    /// the fraction of the area from `xs.0` to `xs.1` and `ys.0` to `ys.1`
+    /// that is covered by raised dots, in dots
+    #[cfg(feature = "image")]
+    fn area(&self, xs: (f64, f64), ys: (f64, f64)) -> f64 {
+        let overlap = |(start, end): (f64, f64), dot: u32| {
+            (end.min(dot as f64 + 1.0) - start.max(dot as f64)).max(0.0)
+        };
+        let x1 = (xs.1.ceil() as u32).min(self.dot_width);
+        let y1 = (ys.1.ceil() as u32).min(self.dot_height);
+
+        let mut raised = 0.0;
+        for y in ys.0 as u32..y1 {
+            for x in xs.0 as u32..x1 {
+                if self.get_dot(x, y) == Some(true) {
+                    raised += overlap(xs, x) * overlap(ys, y);
+                }
+            }
+        }
+        raised / ((xs.1 - xs.0) * (ys.1 - ys.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_resize() {
+        let img: BrailleImg = "⣿⠀\n⠀⣿".parse().unwrap();
+        assert_eq!(
+            img.resize(8, 16, ResizeMethod::Nearest)
+                .unwrap()
+                .as_str(false, true),
+            "⣿⣿⠀⠀\n⣿⣿⠀⠀\n⠀⠀⣿⣿\n⠀⠀⣿⣿"
+        );
+        assert_eq!(
+            img.resize(2, 4, ResizeMethod::Nearest)
+                .unwrap()
+                .as_str(false, true),
+            "⢣"
+        );
+        assert_eq!(img.resize(4, 8, ResizeMethod::Nearest).unwrap(), img);
+
+        let resized = img.resize(3, 5, ResizeMethod::Nearest).unwrap();
+        assert_eq!((resized.width(), resized.height()), (3, 5));
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn area_resize() {
+        let img: BrailleImg = "⣿⠀\n⠀⣿".parse().unwrap();
+        assert_eq!(
+            img.resize(8, 16, ResizeMethod::Area)
+                .unwrap()
+                .as_str(false, true),
+            "⣿⣿⠀⠀\n⣿⣿⠀⠀\n⠀⠀⣿⣿\n⠀⠀⣿⣿"
+        );
+
+        // a checkerboard averages out to gray, which keeps about half of its
+        // dots
+        let mut checkers = BrailleImg::new(16, 16);
+        for y in 0..16 {
+            for x in (y % 2..16).step_by(2) {
+                checkers.set_dot(x, y, true).unwrap();
+            }
+        }
+        let resized = checkers.resize(8, 8, ResizeMethod::Area).unwrap();
+        let raised = (0..8)
+            .flat_map(|y| (0..8).map(move |x| (x, y)))
+            .filter(|&(x, y)| resized.get_dot(x, y) == Some(true))
+            .count();
+        assert_eq!(raised, 32);
+        assert_eq!(
+            checkers
+                .resize(8, 8, ResizeMethod::Nearest)
+                .unwrap()
+                .as_str(false, true),
+            "⣿⣿⣿⣿\n⣿⣿⣿⣿"
+        );
+    }
+
+    #[test]
+    fn zero_sizes() {
+        let img: BrailleImg = "⣿⠀\n⠀⣿".parse().unwrap();
+        assert!(matches!(
+            img.resize(0, 8, ResizeMethod::Nearest),
+            Err(Error::ZeroDimension(0, 8))
+        ));
+        assert!(matches!(
+            img.resize(4, 0, ResizeMethod::default()),
+            Err(Error::ZeroDimension(4, 0))
+        ));
+        #[cfg(feature = "image")]
+        assert!(matches!(
+            img.resize(0, 0, ResizeMethod::Area),
+            Err(Error::ZeroDimension(0, 0))
+        ));
+    }
+}
@@ -0,0 +1,274 @@
+use image::{GrayImage, Luma};
+
+use super::BrailleImg;
+use crate::dithering::{Ditherer, Threshold};
+
+/// subsamples per dot along each axis when estimating how much of a dot a
+/// shape covers
+const SAMPLES: u32 = 4;
+
+/// how much of each dot is covered by shapes, from 0.0 to 1.0, which can be
+/// dithered onto a [BrailleImg] with [BrailleImg::draw_coverage] to draw
+/// smooth edges and gray fills.
+///
+/// coordinates are in dots, where the dot at (x, y) covers the area from x
+/// to x + 1 and from y to y + 1, so the center of a dot is at (x + 0.5,
+/// y + 0.5). shapes are clipped silently, and are drawn over previous ones
+/// with an `intensity` from 0.0 to 1.0, where 1.0 covers them completely
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coverage {
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+}
+
+impl Coverage {
+    /// creates an empty coverage buffer that is `width` by `height` dots
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            values: vec![0.0; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// how much the dot at `x` and `y` is covered, or [None] if it is out of
+    /// bounds
+    pub fn get(&self, x: u32, y: u32) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.values[(x + y * self.width) as usize])
+    }
+
+    /// draws `intensity` over the dot at `x` and `y`, where `amount` of it is
+    /// covered
+    fn blend(&mut self, x: u32, y: u32, amount: f32, intensity: f32) {
+        let value = &mut self.values[(x + y * self.width) as usize];
+        *value += (intensity.clamp(0.0, 1.0) - *value) * amount.clamp(0.0, 1.0);
+    }
+
+    /// covers every dot between `min` and `max` by the fraction of its
+    /// subsamples that are `inside` the shape
+    fn fill_with(
+        &mut self,
+        min: (f32, f32),
+        max: (f32, f32),
+        intensity: f32,
+        inside: impl Fn(f32, f32) -> bool,
+    ) {
+        let x0 = min.0.floor().max(0.0) as u32;
+        let y0 = min.1.floor().max(0.0) as u32;
+        let x1 = (max.0.ceil().max(0.0) as u32).min(self.width);
+        let y1 = (max.1.ceil().max(0.0) as u32).min(self.height);
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let mut hits = 0;
+                for sy in 0..SAMPLES {
+                    for sx in 0..SAMPLES {
+                        let px = x as f32 + (sx as f32 + 0.5) / SAMPLES as f32;
+                        let py = y as f32 + (sy as f32 + 0.5) / SAMPLES as f32;
+                        hits += inside(px, py) as u32;
+                    }
+                }
+                if hits != 0 {
+                    self.blend(x, y, hits as f32 / (SAMPLES * SAMPLES) as f32, intensity);
+                }
+            }
+        }
+    }
+
+    /// fills the rectangle with its top left corner at (`x`, `y`), where dots
+    /// on its edges are covered by exactly the area inside of it
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, intensity: f32) {
+        let x0 = x.floor().max(0.0) as u32;
+        let y0 = y.floor().max(0.0) as u32;
+        let x1 = ((x + width).ceil().max(0.0) as u32).min(self.width);
+        let y1 = ((y + height).ceil().max(0.0) as u32).min(self.height);
+        let overlap = |start: f32, len: f32, dot: u32| {
+            ((start + len).min(dot as f32 + 1.0) - start.max(dot as f32)).max(0.0)
+        };
+
+        for dy in y0..y1 {
+            for dx in x0..x1 {
+                let amount = overlap(x, width, dx) * overlap(y, height, dy);
+                self.blend(dx, dy, amount, intensity);
+            }
+        }
+    }
+
+    /// fills an ellipse around (`cx`, `cy`) with the horizontal radius `rx`
+    /// and the vertical radius `ry`
+    pub fn fill_ellipse(&mut self, cx: f32, cy: f32, rx: f32, ry: f32, intensity: f32) {
+        if rx <= 0.0 || ry <= 0.0 {
+            return;
+        }
+        self.fill_with((cx - rx, cy - ry), (cx + rx, cy + ry), intensity, |x, y| {
+            ((x - cx) / rx).powi(2) + ((y - cy) / ry).powi(2) <= 1.0
+        });
+    }
+
+    /// fills a circle around (`cx`, `cy`)
+    pub fn fill_circle(&mut self, cx: f32, cy: f32, radius: f32, intensity: f32) {
+        self.fill_ellipse(cx, cy, radius, radius, intensity);
+    }
+
+    /// fills the polygon with `points` as its corners, where overlapping parts
+    /// are filled using the even-odd rule
+    pub fn fill_polygon(&mut self, points: &[(f32, f32)], intensity: f32) {
+        if points.len() < 3 {
+            return;
+        }
+        let min = points.iter().fold((f32::INFINITY, f32::INFINITY), |m, p| {
+            (m.0.min(p.0), m.1.min(p.1))
+        });
+        let max = points
+            .iter()
+            .fold((f32::NEG_INFINITY, f32::NEG_INFINITY), |m, p| {
+                (m.0.max(p.0), m.1.max(p.1))
+            });
+
+        self.fill_with(min, max, intensity, |x, y| {
+            let mut inside = false;
+            let mut previous = points[points.len() - 1];
+            for &(px, py) in points {
+                let (qx, qy) = previous;
+                if (py > y) != (qy > y) && x < px + (y - py) * (qx - px) / (qy - py) {
+                    inside = !inside;
+                }
+                previous = (px, py);
+            }
+            inside
+        });
+    }
+
+    /// draws a line from (`x0`, `y0`) to (`x1`, `y1`) that is `thickness`
+    /// dots wide, with round ends
+    pub fn draw_line(
+        &mut self,
+        x0: f32,
+        y0: f32,
+        x1: f32,
+        y1: f32,
+        thickness: f32,
+        intensity: f32,
+    ) {
+        let r = thickness / 2.0;
+        if r <= 0.0 {
+            return;
+        }
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let len2 = dx * dx + dy * dy;
+
+        self.fill_with(
+            (x0.min(x1) - r, y0.min(y1) - r),
+            (x0.max(x1) + r, y0.max(y1) + r),
+            intensity,
+            |x, y| {
+                // distance to the closest point on the line
+                let t = if len2 == 0.0 {
+                    0.0
+                } else {
+                    (((x - x0) * dx + (y - y0) * dy) / len2).clamp(0.0, 1.0)
+                };
+                (x - (x0 + t * dx)).hypot(y - (y0 + t * dy)) <= r
+            },
+        );
+    }
+
+    /// draws lines that are `thickness` dots wide connecting each point to the
+    /// next one
+    pub fn draw_polyline(&mut self, points: &[(f32, f32)], thickness: f32, intensity: f32) {
+        for pair in points.windows(2) {
+            let [(x0, y0), (x1, y1)] = [pair[0], pair[1]];
+            self.draw_line(x0, y0, x1, y1, thickness, intensity);
+        }
+    }
+}
+
+impl BrailleImg {
+    /// dithers `coverage` with `ditherer` and sets the dots that come out
+    /// raised to `raised`, leaving every other dot as it is. the threshold is
+    /// [Threshold::DEFAULT], so ordered ditherers raise as many dots as are
+    /// covered, and [crate::dithering::None] raises dots that are more than
+    /// 96/255 covered. the top left of `coverage` is placed at the top left of
+    /// this image
+    pub fn draw_coverage(&mut self, coverage: &Coverage, ditherer: impl Ditherer, raised: bool) {
+        let mut buffer = GrayImage::from_fn(coverage.width, coverage.height, |x, y| {
+            Luma([(coverage.values[(x + y * coverage.width) as usize] * 255.0).round() as u8])
+        });
+        ditherer.dither(&mut buffer, Threshold::DEFAULT);
+
+        for (x, y, pix) in buffer.enumerate_pixels() {
+            if pix.0[0] == 255 {
+                let _ = self.set_dot(x, y, raised);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dithering::{self, Bayer4x4};
+
+    #[test]
+    fn coverage_shapes() {
+        let mut coverage = Coverage::new(8, 8);
+        coverage.fill_rect(1.5, 1.0, 2.0, 1.0, 1.0);
+        assert_eq!(coverage.get(1, 1), Some(0.5));
+        assert_eq!(coverage.get(2, 1), Some(1.0));
+        assert_eq!(coverage.get(3, 1), Some(0.5));
+        assert_eq!(coverage.get(1, 2), Some(0.0));
+
+        coverage.fill_rect(-10.0, -10.0, 100.0, 100.0, 0.5);
+        assert!(coverage.values.iter().all(|v| *v == 0.5));
+
+        let mut coverage = Coverage::new(16, 16);
+        coverage.fill_circle(8.0, 8.0, 5.0, 1.0);
+        let area: f32 = coverage.values.iter().sum();
+        assert!((area - std::f32::consts::PI * 25.0).abs() < 1.5);
+
+        let mut coverage = Coverage::new(8, 8);
+        coverage.fill_polygon(&[(0.0, 0.0), (8.0, 0.0), (0.0, 8.0)], 1.0);
+        let area: f32 = coverage.values.iter().sum();
+        assert!((area - 32.0).abs() < 1.5);
+        assert_eq!(coverage.get(0, 0), Some(1.0));
+
+        let mut coverage = Coverage::new(8, 8);
+        coverage.draw_line(0.0, 4.0, 8.0, 4.0, 1.0, 1.0);
+        assert!((0..8).all(|x| coverage.get(x, 3) == Some(0.5) && coverage.get(x, 4) == Some(0.5)));
+    }
+
+    #[test]
+    fn dithered_coverage() {
+        let mut coverage = Coverage::new(16, 16);
+        coverage.fill_rect(0.0, 0.0, 16.0, 16.0, 0.5);
+
+        let mut img = BrailleImg::new(16, 16);
+        img.draw_coverage(&coverage, Bayer4x4, true);
+        let raised = (0..16)
+            .flat_map(|y| (0..16).map(move |x| (x, y)))
+            .filter(|&(x, y)| img.get_dot(x, y) == Some(true))
+            .count();
+        assert_eq!(raised, 128);
+
+        // dots that aren't covered enough are left alone without dithering
+        let mut coverage = Coverage::new(4, 4);
+        coverage.fill_rect(0.0, 0.0, 2.0, 4.0, 1.0);
+        coverage.fill_rect(2.0, 0.0, 2.0, 4.0, 0.25);
+        let mut img = BrailleImg::new(4, 4);
+        img.set_dot(3, 3, true).unwrap();
+        img.draw_coverage(&coverage, dithering::None, true);
+        assert_eq!(img.as_str(false, true), "⣿⢀");
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::Write;
+
+use super::BrailleImg;
+use crate::color::Rgb;
+
+/// options for rendering a [BrailleImg] as SVG with [BrailleImg::as_svg]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgOptions {
+    dot_radius: f32,
+    spacing: f32,
+    foreground: Rgb,
+    background: Option<Rgb>,
+    unraised: Option<Rgb>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            dot_radius: 1.5,
+            spacing: 4.0,
+            foreground: Rgb::new(0, 0, 0),
+            background: None,
+            unraised: None,
+        }
+    }
+}
+
+impl SvgOptions {
+    /// radius of each dot, defaults to 1.5
+    pub fn dot_radius(mut self, radius: f32) -> Self {
+        self.dot_radius = radius;
+        self
+    }
+
+    /// distance between the centers of neighbouring dots, defaults to 4.0
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// color of raised dots in characters without a foreground color,
+    /// defaults to black
+    pub fn foreground(mut self, color: Rgb) -> Self {
+        self.foreground = color;
+        self
+    }
+
+    /// color filling the whole image behind the dots, defaults to none, which
+    /// leaves it transparent
+    pub fn background(mut self, color: Option<Rgb>) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// if set, dots that aren't raised are also drawn with this color, which
+    /// should usually be a faint one, defaults to none
+    pub fn unraised(mut self, color: Option<Rgb>) -> Self {
+        self.unraised = color;
+        self
+    }
+}
+
+impl BrailleImg {
+    /// renders this image as an SVG document that draws every raised dot as
+    /// a circle, so it doesn't depend on how a font renders braille characters.
+    ///
+    /// the colors of characters are used if this image has any, where the
+    /// background color of a character fills its whole cell. the output only
+    /// depends on the image and the options, so it can be compared directly
+    pub fn as_svg(&self, options: &SvgOptions) -> String {
+        let s = options.spacing;
+        let width = self.dot_width as f32 * s;
+        let height = self.dot_height as f32 * s;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+        );
+        if let Some(bg) = options.background {
+            let _ = writeln!(
+                svg,
+                "<rect width=\"100%\" height=\"100%\" fill=\"#{bg:x}\"/>"
+            );
+        }
+
+        if let Some(colors) = &self.colors {
+            for (i, color) in colors.iter().enumerate() {
+                if let Some(bg) = color.background {
+                    let col = i as u32 % self.char_width;
+                    let row = i as u32 / self.char_width;
+                    let _ = writeln!(
+                        svg,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#{bg:x}\"/>",
+                        (col * 2) as f32 * s,
+                        (row * 4) as f32 * s,
+                        2.0 * s,
+                        4.0 * s,
+                    );
+                }
+            }
+        }
+
+        if let Some(unraised) = options.unraised {
+            let _ = writeln!(svg, "<g fill=\"#{unraised:x}\">");
+            self.write_circles(&mut svg, options, false);
+            svg.push_str("</g>\n");
+        }
+        let _ = writeln!(svg, "<g fill=\"#{:x}\">", options.foreground);
+        self.write_circles(&mut svg, options, true);
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+
+    /// writes a circle for every dot that is raised or not, where raised dots
+    /// get the foreground color of their character if there is one
+    fn write_circles(&self, svg: &mut String, options: &SvgOptions, raised: bool) {
+        let s = options.spacing;
+        for y in 0..self.dot_height {
+            for x in 0..self.dot_width {
+                if self.get_dot(x, y) != Some(raised) {
+                    continue;
+                }
+                let _ = write!(
+                    svg,
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\"",
+                    (x as f32 + 0.5) * s,
+                    (y as f32 + 0.5) * s,
+                    options.dot_radius
+                );
+                let fill = self
+                    .cell_color(x / 2, y / 4)
+                    .and_then(|c| c.foreground)
+                    .filter(|_| raised);
+                if let Some(fg) = fill {
+                    let _ = write!(svg, " fill=\"#{fg:x}\"");
+                }
+                svg.push_str("/>\n");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::CellColor;
+
+    #[test]
+    fn svg_snapshot() {
+        let mut img = BrailleImg::new(4, 1);
+        img.set_dot(0, 0, true).unwrap();
+        img.set_dot(3, 0, true).unwrap();
+        img.set_cell_color(
+            1,
+            0,
+            CellColor {
+                foreground: Some(Rgb::new(255, 0, 0)),
+                background: Some(Rgb::new(0, 0, 255)),
+            },
+        )
+        .unwrap();
+
+        let options = SvgOptions::default()
+            .spacing(2.0)
+            .dot_radius(0.75)
+            .background(Some(Rgb::new(255, 255, 255)))
+            .unraised(Some(Rgb::new(238, 238, 238)));
+        assert_eq!(
+            img.as_svg(&options),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"8\" height=\"2\" viewBox=\"0 0 8 2\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\n\
+             <rect x=\"4\" y=\"0\" width=\"4\" height=\"8\" fill=\"#0000ff\"/>\n\
+             <g fill=\"#eeeeee\">\n\
+             <circle cx=\"3\" cy=\"1\" r=\"0.75\"/>\n\
+             <circle cx=\"5\" cy=\"1\" r=\"0.75\"/>\n\
+             </g>\n\
+             <g fill=\"#000000\">\n\
+             <circle cx=\"1\" cy=\"1\" r=\"0.75\"/>\n\
+             <circle cx=\"7\" cy=\"1\" r=\"0.75\" fill=\"#ff0000\"/>\n\
+             </g>\n\
+             </svg>\n"
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use super::{BrailleImg, Error};
+
+/// geometric transforms, which return a new image and work on dots, so they
+/// are exact for any size, even if it isn't a multiple of the 2x4 dots of a
+/// character. colors can only move with whole characters, so each character
+/// of the new image takes the colors of the character its middle dot comes
+/// from
+impl BrailleImg {
+    /// creates a `width` by `height` image where each dot is copied from the
+    /// dot of this image that `source` maps it to, or left lowered if it maps
+    /// to none
+    pub(super) fn remap(
+        &self,
+        width: u32,
+        height: u32,
+        source: impl Fn(u32, u32) -> Option<(u32, u32)>,
+    ) -> Self {
+        let mut img = BrailleImg::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                if let Some((sx, sy)) = source(x, y) {
+                    if self.get_dot(sx, sy) == Some(true) {
+                        let _ = img.set_dot(x, y, true);
+                    }
+                }
+            }
+        }
+
+        self.remap_colors(&mut img, source);
+        img
+    }
+
+    /// sets the colors of every character of `img` to the colors of the
+    /// character of this image that `source` maps its middle dot to
+    pub(super) fn remap_colors(
+        &self,
+        img: &mut BrailleImg,
+        source: impl Fn(u32, u32) -> Option<(u32, u32)>,
+    ) {
+        let (width, height) = (img.dot_width, img.dot_height);
+        if self.colors.is_some() {
+            for row in 0..img.char_height {
+                for col in 0..img.char_width {
+                    let (x, y) = ((col * 2 + 1).min(width - 1), (row * 4 + 2).min(height - 1));
+                    let color = source(x, y).and_then(|(sx, sy)| self.cell_color(sx / 2, sy / 4));
+                    if let Some(color) = color {
+                        let _ = img.set_cell_color(col, row, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// mirrors the image left to right
+    pub fn flip_horizontal(&self) -> Self {
+        let w = self.dot_width;
+        self.remap(w, self.dot_height, |x, y| Some((w - 1 - x, y)))
+    }
+
+    /// mirrors the image top to bottom
+    pub fn flip_vertical(&self) -> Self {
+        let h = self.dot_height;
+        self.remap(self.dot_width, h, |x, y| Some((x, h - 1 - y)))
+    }
+
+    /// rotates the image by 90 degrees clockwise
+    pub fn rotate90(&self) -> Self {
+        let h = self.dot_height;
+        self.remap(h, self.dot_width, |x, y| Some((y, h - 1 - x)))
+    }
+
+    /// rotates the image by 180 degrees
+    pub fn rotate180(&self) -> Self {
+        let (w, h) = (self.dot_width, self.dot_height);
+        self.remap(w, h, |x, y| Some((w - 1 - x, h - 1 - y)))
+    }
+
+    /// rotates the image by 270 degrees clockwise, or 90 degrees
+    /// counterclockwise
+    pub fn rotate270(&self) -> Self {
+        let w = self.dot_width;
+        self.remap(self.dot_height, w, |x, y| Some((w - 1 - y, x)))
+    }
+
+    /// mirrors the image along the diagonal from its top left to its bottom
+    /// right, swapping rows and columns
+    pub fn transpose(&self) -> Self {
+        self.remap(self.dot_height, self.dot_width, |x, y| Some((y, x)))
+    }
+
+    /// copies the `width` by `height` dots with their top left at (`x`, `y`)
+    /// into a new image
+    /// # Errors
+    /// - [Error::ZeroDimension] if either `width` or `height` is 0
+    /// - [Error::OutOfBounds] with the bottom right corner of the area if it
+    ///   isn't completely inside of this image
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Self, Error> {
+        BrailleImg::check_dimensions(width, height)?;
+        let right = x as u64 + width as u64;
+        let bottom = y as u64 + height as u64;
+        if right > self.dot_width as u64 || bottom > self.dot_height as u64 {
+            return Err(Error::OutOfBounds(
+                right.saturating_sub(1).min(u32::MAX as u64) as u32,
+                bottom.saturating_sub(1).min(u32::MAX as u64) as u32,
+                self.dot_width,
+                self.dot_height,
+            ));
+        }
+        Ok(self.remap(width, height, |dx, dy| Some((x + dx, y + dy))))
+    }
+
+    /// adds lowered dots around the image, `left` and `right` of it and on its
+    /// `top` and `bottom`
+    /// # Errors
+    /// [Error::TooLarge] if the padded image would be too large, where a width
+    /// or height that doesn't fit in a [u32] is capped at [u32::MAX]
+    pub fn pad(&self, left: u32, top: u32, right: u32, bottom: u32) -> Result<Self, Error> {
+        let grow = |size: u32, a: u32, b: u32| size.checked_add(a)?.checked_add(b);
+        let (width, height) = match (
+            grow(self.dot_width, left, right),
+            grow(self.dot_height, top, bottom),
+        ) {
+            (Some(width), Some(height)) => {
+                BrailleImg::check_dimensions(width, height)?;
+                (width, height)
+            }
+            (width, height) => {
+                return Err(Error::TooLarge(
+                    width.unwrap_or(u32::MAX),
+                    height.unwrap_or(u32::MAX),
+                ))
+            }
+        };
+        Ok(self.remap(width, height, |x, y| {
+            Some((x.checked_sub(left)?, y.checked_sub(top)?))
+        }))
+    }
+
+    /// changes the size of the image to `width` by `height` dots, keeping its
+    /// top left where it is, so it is extended with lowered dots on its right
+    /// and bottom, or cut off there if it gets smaller
+    /// # Errors
+    /// the same as [BrailleImg::try_new]
+    pub fn extend(&self, width: u32, height: u32) -> Result<Self, Error> {
+        BrailleImg::check_dimensions(width, height)?;
+        Ok(self.remap(width, height, |x, y| Some((x, y))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{CellColor, Rgb};
+
+    #[test]
+    fn flips_and_rotations() {
+        // 3x5 dots, so neither dimension fills whole characters
+        let mut img = BrailleImg::new(3, 5);
+        for (x, y) in [(0, 0), (1, 0), (2, 0), (0, 1), (0, 4)] {
+            img.set_dot(x, y, true).unwrap();
+        }
+        assert_eq!(img.as_str(false, true), "⠋⠁\n⠁⠀");
+
+        assert_eq!(img.flip_horizontal().as_str(false, true), "⠉⠃\n⠀⠁");
+        assert_eq!(img.flip_vertical().as_str(false, true), "⡁⠀\n⠉⠁");
+        assert_eq!(img.rotate180().as_str(false, true), "⠀⡁\n⠉⠁");
+        let rotated = img.rotate90();
+        assert_eq!((rotated.width(), rotated.height()), (5, 3));
+        assert_eq!(rotated.as_str(false, true), "⠁⠈⠇");
+        assert_eq!(img.rotate270().as_str(false, true), "⠧⠀⠄");
+        assert_eq!(img.transpose().as_str(false, true), "⠏⠀⠁");
+
+        assert_eq!(img.rotate90().rotate270(), img);
+        assert_eq!(img.transpose().transpose(), img);
+        assert_eq!(img.rotate90().rotate90(), img.rotate180());
+        assert_eq!(img.flip_horizontal().flip_vertical(), img.rotate180());
+    }
+
+    #[test]
+    fn crops_and_pads() {
+        let img: BrailleImg = "⣿⠉\n⠛⣤".parse().unwrap();
+        assert_eq!(img.crop(1, 2, 3, 4).unwrap().as_str(false, true), "⡇⠀");
+        assert_eq!(img.crop(0, 0, 4, 8).unwrap(), img);
+        assert!(matches!(
+            img.crop(2, 4, 3, 4),
+            Err(Error::OutOfBounds(4, 7, 4, 8))
+        ));
+
+        let padded = img.pad(1, 2, 3, 4).unwrap();
+        assert_eq!((padded.width(), padded.height()), (8, 14));
+        assert!(matches!(
+            img.pad(0, 1, 0, u32::MAX),
+            Err(Error::TooLarge(4, u32::MAX))
+        ));
+        assert!(matches!(
+            img.pad(0, 0, u32::MAX - 4, u32::MAX - 8),
+            Err(Error::TooLarge(u32::MAX, u32::MAX))
+        ));
+        assert_eq!(padded.crop(1, 2, 4, 8).unwrap(), img);
+        assert_eq!(img.extend(6, 4).unwrap().as_str(false, true), "⣿⠉⠀");
+        assert_eq!(img.extend(1, 1).unwrap().as_str(false, true), "⠁");
+    }
+
+    #[test]
+    fn zero_sizes() {
+        let img: BrailleImg = "⣿⠉\n⠛⣤".parse().unwrap();
+        assert!(matches!(
+            img.crop(1, 2, 0, 4),
+            Err(Error::ZeroDimension(0, 4))
+        ));
+        assert!(matches!(
+            img.crop(4, 8, 0, 0),
+            Err(Error::ZeroDimension(0, 0))
+        ));
+        assert!(matches!(img.extend(6, 0), Err(Error::ZeroDimension(6, 0))));
+        assert!(matches!(
+            img.extend(u32::MAX, u32::MAX),
+            Err(Error::TooLarge(u32::MAX, u32::MAX))
+        ));
+    }
+
+    #[test]
+    fn transform_colors() {
+        let mut img = BrailleImg::new(8, 4);
+        let red = CellColor {
+            foreground: Some(Rgb::new(255, 0, 0)),
+            background: None,
+        };
+        img.set_cell_color(3, 0, red).unwrap();
+
+        let flipped = img.flip_horizontal();
+        assert_eq!(flipped.cell_color(0, 0), Some(red));
+        assert_eq!(flipped.cell_color(3, 0), Some(CellColor::default()));
+        let rotated = img.rotate90();
+        assert_eq!(rotated.cell_color(0, 0), Some(CellColor::default()));
+        assert_eq!(rotated.cell_color(0, 1), Some(red));
+        assert_eq!(rotated.cell_color(1, 1), Some(red));
+        assert!(!BrailleImg::new(4, 4).rotate90().has_colors());
+    }
+}
@@ -1,13 +1,17 @@
 use std::path::PathBuf;
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
+use make_it_braille::{
+    convert::{Background, Channel, GrayMode},
+    dithering::Threshold,
+};
 use reqwest::Url;
 
 pub(crate) mod util;
 
 #[derive(Debug, Parser)]
-#[command(author, version, about)]
+#[command(author, version, about, subcommand_negates_reqs = true)]
 pub struct Args {
     /// width in dots of the output image, defaults to 64, keeps aspect ratio if
     /// only height is defined instead
@@ -24,7 +28,9 @@ pub struct Args {
     #[arg(long, short, long_help, help = "height in dots of output image")]
     pub frame: Option<u32>,
 
-    /// dithering algorithm to use, defaults to the Sierra two-row algorithm
+    /// dithering algorithm to use, defaults to "sierra2", which uses the
+    /// three-row Sierra kernel like it always has. "two-row-sierra" uses the
+    /// two-row kernel
     #[arg(
         long,
         short,
@@ -34,13 +40,123 @@ pub struct Args {
     )]
     pub dithering: DitheringOption,
 
+    /// cutoff between dark and light pixels, either a number from 0 to 255 or
+    /// one of "otsu", "mean" or "median" to compute it from the image
+    #[arg(
+        long,
+        short,
+        long_help,
+        default_value = "96",
+        value_parser = parse_threshold,
+        help = "cutoff between dark and light pixels"
+    )]
+    pub threshold: Threshold,
+
+    /// size in pixels of the neighbourhood used by the adaptive dithering
+    /// algorithms
+    #[arg(long, long_help, default_value = "15", value_parser = validate_greater_than_zero)]
+    #[arg(help = "window size for adaptive thresholding")]
+    pub window: u32,
+
+    /// bias of the adaptive dithering algorithms, subtracted from the local
+    /// mean for adaptive-mean and adaptive-gaussian, or the k parameter of
+    /// niblack and sauvola. each algorithm has its own default
+    #[arg(long, long_help, allow_negative_numbers = true)]
+    #[arg(help = "bias for adaptive thresholding")]
+    pub bias: Option<f32>,
+
+    /// path to a grayscale image to use as a threshold mask for ordered
+    /// dithering, such as a blue noise texture, instead of a dithering algorithm
+    #[arg(
+        long,
+        long_help,
+        conflicts_with = "dithering",
+        help = "use a custom threshold mask image for dithering"
+    )]
+    pub mask: Option<PathBuf>,
+
+    /// scan every other row right to left when using an error-diffusion
+    /// dithering algorithm, which reduces diagonal artifacts
+    #[arg(long, long_help, help = "use serpentine scanning for error-diffusion")]
+    pub serpentine: bool,
+
+    /// what transparent pixels are composited against, either "black", "white",
+    /// a gray value from 0 to 255 or "none" to never raise dots on transparent
+    /// pixels
+    #[arg(
+        long,
+        long_help,
+        default_value = "black",
+        value_parser = parse_background,
+        help = "background for transparent pixels"
+    )]
+    pub background: Background,
+
+    /// how colors are turned into lightness, one of "rec709", "rec601",
+    /// "average", "max", "min", a single channel ("red", "green", "blue" or
+    /// "alpha") or comma-separated red, green and blue weights such as
+    /// "0.5,0.5,0"
+    #[arg(
+        long,
+        long_help,
+        default_value = "rec709",
+        value_parser = parse_gray_mode,
+        help = "how colors are turned into lightness"
+    )]
+    pub gray_mode: GrayMode,
+
+    /// compute luminance and dither in linear light instead of on sRGB values,
+    /// so the density of dots matches the actual brightness of the image
+    #[arg(long, long_help, help = "dither in linear light")]
+    pub linear: bool,
+
+    /// color each character with the average color of its pixels using ANSI
+    /// escape codes, either with 24-bit colors or mapped to the closest colors
+    /// of the 256 or 16 color palettes
+    #[arg(long, long_help, default_value = "none", help = "colored output")]
+    pub color: ColorOption,
+
+    /// also color the background of each character with the average color of
+    /// the pixels that aren't raised dots, only used with --color
+    #[arg(long, long_help, help = "color the background of each character")]
+    pub color_bg: bool,
+
+    /// pick the raised dots of each character by splitting its pixels into
+    /// the two colors that represent them best, used as the foreground and
+    /// background colors, instead of dithering. requires --color
+    #[arg(long, long_help, help = "split each character into two colors")]
+    pub two_color: bool,
+
+    /// output format, either plain text (with ANSI escape codes when colored),
+    /// a self-contained HTML `<pre>` element, an SVG image that draws each dot
+    /// as a circle, or a PNG image of how the characters would look in a
+    /// terminal. HTML, SVG and PNG output use the exact colors of each
+    /// character with any --color mode
+    #[arg(long, long_help, default_value = "text", help = "output format")]
+    pub format: FormatOption,
+
+    /// crop the output to the area with its top left at X,Y that is WIDTH by
+    /// HEIGHT dots, given as "X,Y,WIDTH,HEIGHT". cropping happens before
+    /// flipping and rotating
+    #[arg(long, long_help, value_parser = parse_crop, help = "crop the output, in dots")]
+    pub crop: Option<CropOption>,
+
+    /// mirror the output either horizontally or vertically, before rotating it
+    #[arg(long, long_help, help = "mirror the output")]
+    pub flip: Option<FlipOption>,
+
+    /// rotate the output clockwise by 90, 180 or 270 degrees
+    #[arg(long, long_help, help = "rotate the output clockwise")]
+    pub rotate: Option<RotateOption>,
+
     /// allows blank braille characters, instead of replacing them with a single dot,
     /// which can cause images to appear skewed, especially on windows, even with
     /// a monospace font.
     #[arg(long, short = 'b', long_help, help = "allow blank braille characters")]
     pub allow_blank_chars: bool,
 
-    /// invert dots, making light values in the source image be raised dots instead
+    /// invert dots, making dark values in the source image be raised dots
+    /// instead of light ones
     #[arg(long, short)]
     pub invert: bool,
 
@@ -57,8 +173,43 @@ pub struct Args {
     pub verbose: u8,
 
     /// either the path to a local image file, an http(s) URL to one or "-" to read from stdin
-    #[arg(value_parser = parse_mode)]
-    pub input: Mode,
+    #[arg(value_parser = parse_mode, required = true)]
+    pub input: Option<Mode>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// render text as a braille banner instead of converting an image
+    Text {
+        /// the text to render, where multiple arguments are joined with spaces
+        #[arg(required = true)]
+        text: Vec<String>,
+
+        /// the font to render the text with, either one of the embedded "3x5"
+        /// or "5x7" fonts, or the path to a BDF or PSF font file
+        #[arg(long, long_help, default_value = "5x7", help = "font to use")]
+        #[arg(value_parser = parse_font)]
+        font: FontOption,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum FontOption {
+    Ascii3x5,
+    Ascii5x7,
+    File(PathBuf),
+}
+
+fn parse_font(val: &str) -> Result<FontOption, &'static str> {
+    match val {
+        "3x5" => Ok(FontOption::Ascii3x5),
+        "5x7" => Ok(FontOption::Ascii5x7),
+        path if PathBuf::from(path).is_file() => Ok(FontOption::File(PathBuf::from(path))),
+        _ => Err("must be either 3x5, 5x7 or the path to a font file"),
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -112,15 +263,157 @@ fn validate_greater_than_zero(val: &str) -> Result<u32, &'static str> {
     }
 }
 
+fn parse_crop(val: &str) -> Result<CropOption, &'static str> {
+    const ERR: &str = "must be four comma-separated numbers X,Y,WIDTH,HEIGHT";
+    let values: Vec<u32> = val
+        .split(',')
+        .map(|v| v.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| ERR)?;
+    match values[..] {
+        [_, _, 0, _] | [_, _, _, 0] => Err("the width and height of the area cannot be 0"),
+        [x, y, width, height] => Ok(CropOption {
+            x,
+            y,
+            width,
+            height,
+        }),
+        _ => Err(ERR),
+    }
+}
+
+fn parse_threshold(val: &str) -> Result<Threshold, &'static str> {
+    match val.to_ascii_lowercase().as_str() {
+        "otsu" => Ok(Threshold::Otsu),
+        "mean" => Ok(Threshold::Mean),
+        "median" => Ok(Threshold::Median),
+        other => other
+            .parse::<u8>()
+            .map(Threshold::Fixed)
+            .map_err(|_| "must be a number from 0 to 255, otsu, mean or median"),
+    }
+}
+
+fn parse_background(val: &str) -> Result<Background, &'static str> {
+    match val.to_ascii_lowercase().as_str() {
+        "black" => Ok(Background::Black),
+        "white" => Ok(Background::White),
+        "none" => Ok(Background::NoDot),
+        other => other
+            .parse::<u8>()
+            .map(Background::Gray)
+            .map_err(|_| "must be black, white, none or a number from 0 to 255"),
+    }
+}
+
+fn parse_gray_mode(val: &str) -> Result<GrayMode, &'static str> {
+    const ERR: &str = "must be a gray mode or three comma-separated weights";
+    match val.to_ascii_lowercase().as_str() {
+        "rec709" => Ok(GrayMode::Rec709),
+        "rec601" => Ok(GrayMode::Rec601),
+        "average" => Ok(GrayMode::Average),
+        "max" => Ok(GrayMode::Max),
+        "min" => Ok(GrayMode::Min),
+        "red" | "r" => Ok(GrayMode::Channel(Channel::Red)),
+        "green" | "g" => Ok(GrayMode::Channel(Channel::Green)),
+        "blue" | "b" => Ok(GrayMode::Channel(Channel::Blue)),
+        "alpha" | "a" => Ok(GrayMode::Channel(Channel::Alpha)),
+        other => {
+            let weights: Vec<f32> = other
+                .split(',')
+                .map(|w| w.trim().parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| ERR)?;
+            match weights[..] {
+                [r, g, b] => Ok(GrayMode::Weights([r, g, b])),
+                _ => Err(ERR),
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, ValueEnum, Default)]
 pub enum DitheringOption {
     #[default]
     #[value(alias("s2"))]
     Sierra2,
+    #[value(alias("s3"))]
+    Sierra3,
+    #[value(alias("s2r"))]
+    TwoRowSierra,
+    #[value(alias("sl"))]
+    SierraLite,
+    #[value(alias("fs"))]
+    FloydSteinberg,
+    #[value(alias("a"))]
+    Atkinson,
+    #[value(alias("jjn"))]
+    JarvisJudiceNinke,
+    Stucki,
+    Burkes,
+    #[value(alias("bn"))]
+    BlueNoise,
+    #[value(alias("b16"))]
+    Bayer16x16,
+    #[value(alias("b8"))]
+    Bayer8x8,
     #[value(alias("b4"))]
     Bayer4x4,
     #[value(alias("b2"))]
     Bayer2x2,
+    #[value(alias("am"))]
+    AdaptiveMean,
+    #[value(alias("ag"))]
+    AdaptiveGaussian,
+    Niblack,
+    Sauvola,
     #[value(alias("n"))]
     None,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum ColorOption {
+    #[default]
+    None,
+    #[value(alias("24bit"))]
+    Truecolor,
+    #[value(name = "256")]
+    Ansi256,
+    #[value(name = "16")]
+    Ansi16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum FormatOption {
+    #[default]
+    Text,
+    Html,
+    Svg,
+    Png,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CropOption {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FlipOption {
+    #[value(alias("h"))]
+    Horizontal,
+    #[value(alias("v"))]
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RotateOption {
+    #[value(name = "90")]
+    Rotate90,
+    #[value(name = "180")]
+    Rotate180,
+    #[value(name = "270")]
+    Rotate270,
+}
@@ -15,6 +15,10 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error(transparent)]
     Fetch(#[from] FetchError),
+    #[error(transparent)]
+    Font(#[from] make_it_braille::text::FontError),
+    #[error(transparent)]
+    Braille(#[from] make_it_braille::Error),
 }
 
 #[derive(Debug, Error)]
@@ -0,0 +1,306 @@
+//! colors, color space conversions and ANSI escape codes
+
+use std::{fmt::Write, sync::OnceLock};
+
+/// an 8-bit sRGB color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// formats the color as six hex digits, like `ff8000`, as used in HTML and SVG
+impl std::fmt::LowerHex for Rgb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl Rgb {
+    /// the color in linear light, with channels from 0.0 to 1.0
+    pub fn to_linear(&self) -> [f32; 3] {
+        [self.r, self.g, self.b].map(srgb8_to_linear)
+    }
+
+    /// converts a color in linear light, with channels from 0.0 to 1.0, back
+    /// to sRGB
+    pub fn from_linear(linear: [f32; 3]) -> Self {
+        let [r, g, b] = linear.map(|c| (linear_to_srgb(c.clamp(0.0, 1.0)) * 255.0).round() as u8);
+        Self { r, g, b }
+    }
+}
+
+impl From<[u8; 3]> for Rgb {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// the colors of a single braille character, where [None] means the
+/// terminal's default color is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CellColor {
+    /// the color of the raised dots
+    pub foreground: Option<Rgb>,
+    /// the color behind the dots
+    pub background: Option<Rgb>,
+}
+
+/// which kind of ANSI escape codes are used for colored output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// 24-bit colors, supported by most modern terminals
+    #[default]
+    TrueColor,
+    /// the xterm 256 color palette, where colors are mapped to the closest
+    /// entry of its color cube or gray ramp
+    Ansi256,
+    /// the basic 16 ANSI colors, which every color terminal supports, though
+    /// their exact values depend on the terminal's theme
+    Ansi16,
+}
+
+impl ColorMode {
+    /// resets all colors and other attributes
+    pub const RESET: &'static str = "\x1b[0m";
+
+    /// maps `color` to what will actually be emitted in this mode
+    pub(crate) fn quantize(&self, color: Option<Rgb>) -> AnsiColor {
+        match (self, color) {
+            (_, None) => AnsiColor::Default,
+            (ColorMode::TrueColor, Some(c)) => AnsiColor::Rgb(c),
+            (ColorMode::Ansi256, Some(c)) => AnsiColor::Indexed(c.nearest_ansi256()),
+            (ColorMode::Ansi16, Some(c)) => AnsiColor::Indexed(c.nearest_ansi16()),
+        }
+    }
+}
+
+/// a color as it is written in an SGR escape sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum AnsiColor {
+    /// the terminal's default color
+    #[default]
+    Default,
+    /// an index into the 256 color palette, where the first 16 use the basic
+    /// SGR codes
+    Indexed(u8),
+    Rgb(Rgb),
+}
+
+impl AnsiColor {
+    /// writes the SGR parameters selecting this as the foreground or the
+    /// background color
+    pub(crate) fn write_params(&self, out: &mut String, background: bool) {
+        let _ = match (self, background) {
+            (AnsiColor::Default, false) => write!(out, "39"),
+            (AnsiColor::Default, true) => write!(out, "49"),
+            (AnsiColor::Indexed(i @ 0..=7), false) => write!(out, "{}", 30 + i),
+            (AnsiColor::Indexed(i @ 0..=7), true) => write!(out, "{}", 40 + i),
+            (AnsiColor::Indexed(i @ 8..=15), false) => write!(out, "{}", 82 + i),
+            (AnsiColor::Indexed(i @ 8..=15), true) => write!(out, "{}", 92 + i),
+            (AnsiColor::Indexed(i), false) => write!(out, "38;5;{i}"),
+            (AnsiColor::Indexed(i), true) => write!(out, "48;5;{i}"),
+            (AnsiColor::Rgb(Rgb { r, g, b }), false) => write!(out, "38;2;{r};{g};{b}"),
+            (AnsiColor::Rgb(Rgb { r, g, b }), true) => write!(out, "48;2;{r};{g};{b}"),
+        };
+    }
+
+    /// writes an SGR escape sequence switching from the `previous` colors to
+    /// `next`, only including the ones that actually changed
+    pub(crate) fn write_transition(
+        out: &mut String,
+        previous: (AnsiColor, AnsiColor),
+        next: (AnsiColor, AnsiColor),
+    ) {
+        let fg_changed = previous.0 != next.0;
+        let bg_changed = previous.1 != next.1;
+        if !fg_changed && !bg_changed {
+            return;
+        }
+        out.push_str("\x1b[");
+        if fg_changed {
+            next.0.write_params(out, false);
+        }
+        if fg_changed && bg_changed {
+            out.push(';');
+        }
+        if bg_changed {
+            next.1.write_params(out, true);
+        }
+        out.push('m');
+    }
+}
+
+/// the default xterm values of the 16 basic ANSI colors
+#[rustfmt::skip]
+pub const ANSI16_PALETTE: [Rgb; 16] = [
+    Rgb::new(0, 0, 0),       Rgb::new(205, 0, 0),     Rgb::new(0, 205, 0),     Rgb::new(205, 205, 0),
+    Rgb::new(0, 0, 238),     Rgb::new(205, 0, 205),   Rgb::new(0, 205, 205),   Rgb::new(229, 229, 229),
+    Rgb::new(127, 127, 127), Rgb::new(255, 0, 0),     Rgb::new(0, 255, 0),     Rgb::new(255, 255, 0),
+    Rgb::new(92, 92, 255),   Rgb::new(255, 0, 255),   Rgb::new(0, 255, 255),   Rgb::new(255, 255, 255),
+];
+
+/// the color of an entry of the xterm 256 color palette, where the first 16
+/// are [ANSI16_PALETTE]
+pub fn ansi256_to_rgb(index: u8) -> Rgb {
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    match index {
+        0..=15 => ANSI16_PALETTE[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            Rgb::new(
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let v = 8 + (index - 232) * 10;
+            Rgb::new(v, v, v)
+        }
+    }
+}
+
+impl Rgb {
+    /// the color in the OKLab perceptual color space, as `[L, a, b]`
+    // the coefficients are kept exactly as they were published
+    #[allow(clippy::excessive_precision)]
+    pub fn to_oklab(&self) -> [f32; 3] {
+        let [r, g, b] = self.to_linear();
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+        [
+            0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        ]
+    }
+
+    /// the perceptual distance to `other`, as the squared euclidean distance
+    /// between both colors in OKLab
+    pub fn distance(&self, other: &Rgb) -> f32 {
+        let (a, b) = (self.to_oklab(), other.to_oklab());
+        a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+    }
+
+    /// the index of the closest color of the xterm 256 color palette,
+    /// skipping the first 16 since those depend on the terminal's theme
+    pub fn nearest_ansi256(&self) -> u8 {
+        static PALETTE: OnceLock<Vec<[f32; 3]>> = OnceLock::new();
+
+        let palette =
+            PALETTE.get_or_init(|| (16..=255).map(|i| ansi256_to_rgb(i).to_oklab()).collect());
+        nearest(self.to_oklab(), palette) as u8 + 16
+    }
+
+    /// the index of the closest of the 16 basic ANSI colors, according to
+    /// [ANSI16_PALETTE]
+    pub fn nearest_ansi16(&self) -> u8 {
+        static PALETTE: OnceLock<Vec<[f32; 3]>> = OnceLock::new();
+
+        let palette = PALETTE.get_or_init(|| ANSI16_PALETTE.iter().map(Rgb::to_oklab).collect());
+        nearest(self.to_oklab(), palette) as u8
+    }
+}
+
+/// the index of the entry of `palette` closest to `color`, all in OKLab
+fn nearest(color: [f32; 3], palette: &[[f32; 3]]) -> usize {
+    let distance =
+        |p: &[f32; 3]| -> f32 { p.iter().zip(color).map(|(a, b)| (a - b) * (a - b)).sum() };
+    palette
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+        .map(|(i, _)| i)
+        .unwrap_or_default()
+}
+
+/// converts an sRGB encoded value from 0.0 to 1.0 to linear light, using the
+/// exact sRGB transfer function rather than a plain 2.2 gamma
+pub fn srgb_to_linear(val: f32) -> f32 {
+    if val <= 0.04045 {
+        val / 12.92
+    } else {
+        ((val + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// converts a linear light value from 0.0 to 1.0 back to sRGB encoding
+pub fn linear_to_srgb(val: f32) -> f32 {
+    if val <= 0.0031308 {
+        val * 12.92
+    } else {
+        1.055 * val.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// same as [srgb_to_linear] for 8-bit values, but uses a lookup table
+pub fn srgb8_to_linear(val: u8) -> f32 {
+    static LUT: OnceLock<[f32; 256]> = OnceLock::new();
+
+    LUT.get_or_init(|| std::array::from_fn(|i| srgb_to_linear(i as f32 / 255.0)))[val as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ansi256_to_rgb, linear_to_srgb, srgb8_to_linear, srgb_to_linear, AnsiColor, ColorMode, Rgb,
+    };
+
+    #[test]
+    fn round_trip() {
+        for i in 0..=255u8 {
+            let linear = srgb8_to_linear(i);
+            let srgb = (linear_to_srgb(linear) * 255.0).round() as u8;
+            assert_eq!(i, srgb);
+        }
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-6);
+        // middle gray in sRGB is roughly 21.4% linear light
+        assert!((srgb_to_linear(0.5) - 0.214).abs() < 1e-3);
+    }
+
+    #[test]
+    fn palettes() {
+        assert_eq!(ansi256_to_rgb(16), Rgb::new(0, 0, 0));
+        assert_eq!(ansi256_to_rgb(196), Rgb::new(255, 0, 0));
+        assert_eq!(ansi256_to_rgb(255), Rgb::new(238, 238, 238));
+
+        assert_eq!(Rgb::new(250, 5, 5).nearest_ansi256(), 196);
+        assert_eq!(Rgb::new(128, 128, 128).nearest_ansi256(), 244);
+        assert_eq!(Rgb::new(250, 5, 5).nearest_ansi16(), 9);
+        assert_eq!(Rgb::new(10, 10, 10).nearest_ansi16(), 0);
+    }
+
+    #[test]
+    fn sgr_transitions() {
+        let red = Some(Rgb::new(255, 0, 0));
+        let mut out = String::new();
+        let previous = (AnsiColor::Default, AnsiColor::Default);
+
+        let next = (ColorMode::TrueColor.quantize(red), AnsiColor::Default);
+        AnsiColor::write_transition(&mut out, previous, next);
+        assert_eq!(out, "\x1b[38;2;255;0;0m");
+
+        out.clear();
+        AnsiColor::write_transition(&mut out, next, next);
+        assert_eq!(out, "");
+
+        let next_bg = (next.0, ColorMode::Ansi16.quantize(red));
+        AnsiColor::write_transition(&mut out, next, next_bg);
+        assert_eq!(out, "\x1b[101m");
+
+        out.clear();
+        let next_256 = (ColorMode::Ansi256.quantize(red), AnsiColor::Default);
+        AnsiColor::write_transition(&mut out, next_bg, next_256);
+        assert_eq!(out, "\x1b[38;5;196;49m");
+    }
+}
@@ -0,0 +1,676 @@
+//! configurable conversion of images into [BrailleImg]s
+//!
+//! ```rust no_run
+//! use make_it_braille::{
+//!     convert::Raise,
+//!     dithering::{FloydSteinberg, Threshold},
+//!     BrailleImg,
+//! };
+//!
+//! let img = image::open("image.png").unwrap();
+//!
+//! let braille = BrailleImg::converter()
+//!     .ditherer(FloydSteinberg)
+//!     .threshold(Threshold::Otsu)
+//!     .raise(Raise::Light)
+//!     .convert(img);
+//! ```
+
+use image::{GenericImageView, GrayImage, Luma, Rgba};
+
+use crate::{
+    color::{self, CellColor, Rgb},
+    dithering::{Ditherer, Sierra2Row, Threshold},
+    BrailleImg,
+};
+
+/// which pixels of the source image become raised dots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Raise {
+    /// dark pixels are raised, like ink on paper
+    #[default]
+    Dark,
+    /// light pixels are raised, like text on a dark terminal
+    Light,
+}
+
+/// what transparent pixels are composited against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Background {
+    #[default]
+    Black,
+    White,
+    /// a gray with the given sRGB value
+    Gray(u8),
+    /// pixels that are more than half transparent never become raised dots,
+    /// regardless of [Converter::raise], and the color of the rest is used
+    /// as-is
+    NoDot,
+}
+
+impl Background {
+    /// the lightness of the background from 0.0 to 1.0
+    fn lightness(&self, linear: bool) -> Option<f32> {
+        let srgb = match self {
+            Background::Black => 0,
+            Background::White => 255,
+            Background::Gray(v) => *v,
+            Background::NoDot => return None,
+        };
+        Some(if linear {
+            color::srgb8_to_linear(srgb)
+        } else {
+            srgb as f32 / 255.0
+        })
+    }
+}
+
+/// a single channel of an RGBA pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    Alpha,
+}
+
+/// how the color of each pixel is turned into a single lightness value
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GrayMode {
+    /// luma with the Rec. 709 coefficients, which match sRGB primaries
+    #[default]
+    Rec709,
+    /// luma with the Rec. 601 coefficients
+    Rec601,
+    /// the average of the red, green and blue channels
+    Average,
+    /// the brightest of the red, green and blue channels
+    Max,
+    /// the darkest of the red, green and blue channels
+    Min,
+    /// a single channel, where [Channel::Alpha] ignores the background and
+    /// uses the opacity of each pixel directly
+    Channel(Channel),
+    /// custom red, green and blue weights, which should add up to 1.0
+    Weights([f32; 3]),
+}
+
+impl GrayMode {
+    /// the lightness of an RGB color with channels from 0.0 to 1.0, or [None]
+    /// if the lightness doesn't depend on the color at all
+    fn lightness(&self, [r, g, b]: [f32; 3]) -> Option<f32> {
+        let weighted = |[wr, wg, wb]: [f32; 3]| r * wr + g * wg + b * wb;
+        Some(match self {
+            GrayMode::Rec709 => weighted([0.2126, 0.7152, 0.0722]),
+            GrayMode::Rec601 => weighted([0.299, 0.587, 0.114]),
+            GrayMode::Average => (r + g + b) / 3.0,
+            GrayMode::Max => r.max(g).max(b),
+            GrayMode::Min => r.min(g).min(b),
+            GrayMode::Channel(Channel::Red) => r,
+            GrayMode::Channel(Channel::Green) => g,
+            GrayMode::Channel(Channel::Blue) => b,
+            GrayMode::Channel(Channel::Alpha) => return None,
+            GrayMode::Weights(weights) => weighted(*weights),
+        })
+    }
+}
+
+/// which colors are stored on each character of the converted image, see
+/// [BrailleImg::cell_color]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CellColors {
+    /// no colors at all
+    #[default]
+    None,
+    /// the average color of the pixels under the raised dots of each
+    /// character, or of all of its pixels if none are raised
+    Foreground,
+    /// the same foreground as [CellColors::Foreground], plus the average color
+    /// of the pixels that aren't raised dots as the background
+    ForegroundBackground,
+}
+
+/// a builder for converting images into [BrailleImg]s, created with
+/// [BrailleImg::converter]
+#[derive(Debug, Clone)]
+pub struct Converter<D = Sierra2Row> {
+    ditherer: D,
+    options: ConversionOptions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct ConversionOptions {
+    threshold: Threshold,
+    raise: Raise,
+    linear: bool,
+    background: Background,
+    gray_mode: GrayMode,
+    colors: CellColors,
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        Self {
+            ditherer: Sierra2Row,
+            options: ConversionOptions::default(),
+        }
+    }
+}
+
+impl<D: Ditherer> Converter<D> {
+    /// the dithering algorithm used to pick which dots are raised, defaults
+    /// to [Sierra2Row]
+    pub fn ditherer<E: Ditherer>(self, ditherer: E) -> Converter<E> {
+        Converter {
+            ditherer,
+            options: self.options,
+        }
+    }
+
+    /// the cutoff between dark and light pixels, either a fixed value or a
+    /// [Threshold] computed from the image itself, defaults to
+    /// [Threshold::DEFAULT]
+    pub fn threshold(mut self, threshold: impl Into<Threshold>) -> Self {
+        self.options.threshold = threshold.into();
+        self
+    }
+
+    /// which pixels become raised dots, defaults to [Raise::Dark]
+    pub fn raise(mut self, raise: Raise) -> Self {
+        self.options.raise = raise;
+        self
+    }
+
+    /// if true, luminance is computed and dithered in linear light instead of
+    /// on the sRGB encoded values, so the density of raised dots matches the
+    /// actual brightness of the image. a fixed threshold is still given in
+    /// sRGB terms
+    pub fn linear(mut self, linear: bool) -> Self {
+        self.options.linear = linear;
+        self
+    }
+
+    /// what transparent pixels are composited against, defaults to
+    /// [Background::Black]
+    pub fn background(mut self, background: Background) -> Self {
+        self.options.background = background;
+        self
+    }
+
+    /// how colors are turned into lightness values, defaults to
+    /// [GrayMode::Rec709]
+    pub fn gray_mode(mut self, gray_mode: GrayMode) -> Self {
+        self.options.gray_mode = gray_mode;
+        self
+    }
+
+    /// which colors from the source image are stored on each character,
+    /// defaults to [CellColors::None]
+    pub fn colors(mut self, colors: CellColors) -> Self {
+        self.options.colors = colors;
+        self
+    }
+
+    /// converts `img` to braille, where each pixel becomes a single dot
+    pub fn convert(&self, img: impl GenericImageView<Pixel = Rgba<u8>>) -> BrailleImg {
+        let options = &self.options;
+        let (mut gray_img, transparent) = self.lightness(&img);
+
+        let threshold = match options.threshold {
+            Threshold::Fixed(t) if options.linear => {
+                Threshold::Fixed((color::srgb_to_linear(t as f32 / 255.0) * 255.0).round() as u8)
+            }
+            t => t,
+        };
+        let threshold = threshold.resolve(&gray_img);
+        self.ditherer.dither(&mut gray_img, threshold);
+
+        let mut braille_img = BrailleImg::new(gray_img.width(), gray_img.height());
+        // this is fine since the dimensions of gray_img are always the same as braille_img's
+        for (x, y, pix) in gray_img.enumerate_pixels() {
+            // every pixel is either 0 or 255 after dithering, and ditherers
+            // that compute their own thresholds don't line up with this one
+            let light = pix.0[0] == 255;
+            if light == (options.raise == Raise::Light)
+                && !transparent[(x + y * gray_img.width()) as usize]
+            {
+                let _ = braille_img.set_dot(x, y, true);
+            }
+        }
+        if options.colors != CellColors::None {
+            self.store_colors(&img, &mut braille_img);
+        }
+        braille_img
+    }
+
+    /// averages the source colors of each character, in linear light
+    fn store_colors(
+        &self,
+        img: &impl GenericImageView<Pixel = Rgba<u8>>,
+        braille_img: &mut BrailleImg,
+    ) {
+        let (width, height) = img.dimensions();
+
+        for row in 0..braille_img.char_height() {
+            for col in 0..braille_img.char_width() {
+                let mut raised = ColorSum::default();
+                let mut flat = ColorSum::default();
+                for y in row * 4..(row * 4 + 4).min(height) {
+                    for x in col * 2..(col * 2 + 2).min(width) {
+                        let Some(color) = self.linear_color(img.get_pixel(x, y)) else {
+                            continue;
+                        };
+                        if braille_img.get_dot(x, y) == Some(true) {
+                            raised.add(color);
+                        } else {
+                            flat.add(color);
+                        }
+                    }
+                }
+
+                let all = raised.merged(&flat);
+                let color = CellColor {
+                    foreground: raised.average().or(all.average()),
+                    background: match self.options.colors {
+                        CellColors::ForegroundBackground => flat.average().or(all.average()),
+                        _ => None,
+                    },
+                };
+                let _ = braille_img.set_cell_color(col, row, color);
+            }
+        }
+    }
+
+    /// converts `img` to braille by splitting the pixels of every character
+    /// into the two groups of colors that represent them best, which become
+    /// the foreground and background colors of the character, with the dots
+    /// of the darker group raised, or of the lighter one with [Raise::Light].
+    ///
+    /// the ditherer, threshold and color options are ignored, since the dots
+    /// only depend on how the colors of each character are split
+    pub fn convert_two_color(&self, img: impl GenericImageView<Pixel = Rgba<u8>>) -> BrailleImg {
+        let (width, height) = img.dimensions();
+        let mut braille_img = BrailleImg::new(width, height);
+
+        for row in 0..braille_img.char_height() {
+            for col in 0..braille_img.char_width() {
+                let mut positions = Vec::with_capacity(8);
+                let mut colors = Vec::with_capacity(8);
+                for y in row * 4..(row * 4 + 4).min(height) {
+                    for x in col * 2..(col * 2 + 2).min(width) {
+                        if let Some(color) = self.linear_color(img.get_pixel(x, y)) {
+                            positions.push((x, y));
+                            colors.push(color);
+                        }
+                    }
+                }
+                if colors.is_empty() {
+                    continue;
+                }
+
+                let split = best_split(&colors);
+                let mut groups = [ColorSum::default(); 2];
+                for (i, color) in colors.iter().enumerate() {
+                    groups[(split >> i & 1) as usize].add(*color);
+                }
+                let [zero, one] = groups;
+
+                let one_darker = one.luminance() < zero.luminance();
+                let one_raised =
+                    one.count == 0 || one_darker == (self.options.raise == Raise::Dark);
+                let (raised_mask, raised, flat) = if one_raised {
+                    (split, one, zero)
+                } else {
+                    (!split, zero, one)
+                };
+
+                for (i, (x, y)) in positions.iter().enumerate() {
+                    if raised_mask & (1 << i) != 0 {
+                        let _ = braille_img.set_dot(*x, *y, true);
+                    }
+                }
+                let all = raised.merged(&flat);
+                let _ = braille_img.set_cell_color(
+                    col,
+                    row,
+                    CellColor {
+                        foreground: raised.average().or(all.average()),
+                        background: flat.average().or(all.average()),
+                    },
+                );
+            }
+        }
+        braille_img
+    }
+
+    /// the color of a pixel composited against the background in linear
+    /// light, or [None] if it counts as transparent with [Background::NoDot]
+    fn linear_color(&self, pix: Rgba<u8>) -> Option<[f32; 3]> {
+        let [r, g, b, a] = pix.0;
+        let color = Rgb::new(r, g, b).to_linear();
+        let alpha = a as f32 / 255.0;
+        match self.options.background.lightness(true) {
+            Some(bg) => Some(color.map(|c| c * alpha + bg * (1.0 - alpha))),
+            None if alpha < 0.5 => None,
+            None => Some(color),
+        }
+    }
+
+    /// the lightness of every pixel, along with which pixels are considered
+    /// transparent when using [Background::NoDot]
+    fn lightness(&self, img: &impl GenericImageView<Pixel = Rgba<u8>>) -> (GrayImage, Vec<bool>) {
+        let options = &self.options;
+        let mut gray_img = GrayImage::new(img.width(), img.height());
+        let mut transparent = vec![false; (img.width() * img.height()) as usize];
+        let background = options.background.lightness(options.linear);
+        // transparent pixels get the value furthest from being a raised dot,
+        // so they don't attract any error from their neighbours either
+        let no_dot = match options.raise {
+            Raise::Dark => 1.0,
+            Raise::Light => 0.0,
+        };
+
+        for (x, y, pix) in img.pixels() {
+            let [r, g, b, a] = pix.0;
+            let [r, g, b] = if options.linear {
+                [r, g, b].map(color::srgb8_to_linear)
+            } else {
+                [r, g, b].map(|c| c as f32 / 255.0)
+            };
+            let alpha = a as f32 / 255.0;
+            let Some(luminance) = options.gray_mode.lightness([r, g, b]) else {
+                gray_img.put_pixel(x, y, Luma([a]));
+                continue;
+            };
+
+            let lightness = match background {
+                Some(bg) => luminance * alpha + bg * (1.0 - alpha),
+                None if alpha < 0.5 => {
+                    transparent[(x + y * img.width()) as usize] = true;
+                    no_dot
+                }
+                None => luminance,
+            };
+            let lightness = (lightness * 255.0).clamp(0.0, 255.0).round() as u8;
+            gray_img.put_pixel(x, y, Luma([lightness]));
+        }
+        (gray_img, transparent)
+    }
+}
+
+/// a running sum of colors in linear light
+#[derive(Debug, Clone, Copy, Default)]
+struct ColorSum {
+    sum: [f32; 3],
+    count: u32,
+}
+
+impl ColorSum {
+    fn add(&mut self, color: [f32; 3]) {
+        for (sum, c) in self.sum.iter_mut().zip(color) {
+            *sum += c;
+        }
+        self.count += 1;
+    }
+
+    fn merged(&self, other: &ColorSum) -> ColorSum {
+        let mut merged = *self;
+        for (sum, c) in merged.sum.iter_mut().zip(other.sum) {
+            *sum += c;
+        }
+        merged.count += other.count;
+        merged
+    }
+
+    /// the Rec. 709 luminance of the average color
+    fn luminance(&self) -> f32 {
+        let [r, g, b] = self.sum.map(|c| c / self.count.max(1) as f32);
+        r * 0.2126 + g * 0.7152 + b * 0.0722
+    }
+
+    fn average(&self) -> Option<Rgb> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(Rgb::from_linear(self.sum.map(|c| c / self.count as f32)))
+    }
+}
+
+/// the split of up to 8 `colors` into two groups with the smallest squared
+/// error around the mean of each group, as a bitmask of the second group
+fn best_split(colors: &[[f32; 3]]) -> u8 {
+    let squared_norm = |c: [f32; 3]| c.iter().map(|v| v * v).sum::<f32>();
+    let total = colors.iter().fold([0.0; 3], |acc, c| {
+        [acc[0] + c[0], acc[1] + c[1], acc[2] + c[2]]
+    });
+
+    let mut best = (0, f32::MAX);
+    // the last color always stays in the first group, since swapping both
+    // groups results in the same split
+    for split in 0..(1u16 << (colors.len().max(1) - 1)) {
+        let mut sum = [0.0; 3];
+        let mut count = 0;
+        for (i, color) in colors.iter().enumerate() {
+            if split >> i & 1 != 0 {
+                for (s, c) in sum.iter_mut().zip(color) {
+                    *s += c;
+                }
+                count += 1;
+            }
+        }
+        let rest = [total[0] - sum[0], total[1] - sum[1], total[2] - sum[2]];
+        let rest_count = colors.len() - count;
+
+        // the squared error of a group is Σ|c|² - |Σc|² / n, and Σ|c|² is the
+        // same for every split, so only the second term has to be compared
+        let mut score = 0.0;
+        if count > 0 {
+            score -= squared_norm(sum) / count as f32;
+        }
+        if rest_count > 0 {
+            score -= squared_norm(rest) / rest_count as f32;
+        }
+        // ignores rounding errors so flat colors don't get split arbitrarily
+        if score < best.1 - 1e-5 {
+            best = (split as u8, score);
+        }
+    }
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use super::{Background, CellColors, Channel, GrayMode, Raise};
+    use crate::color::{CellColor, Rgb};
+    use crate::{dithering, BrailleImg};
+
+    #[test]
+    fn raise() {
+        let img = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+
+        let braille = BrailleImg::converter()
+            .ditherer(dithering::None)
+            .convert(img.clone());
+        assert_eq!(braille.get_dot(0, 0), Some(false));
+
+        let braille = BrailleImg::converter()
+            .ditherer(dithering::None)
+            .raise(Raise::Light)
+            .convert(img.clone());
+        assert_eq!(braille.get_dot(0, 0), Some(true));
+    }
+
+    #[test]
+    fn dithered_pixels_decide_dots() {
+        // the median of a mostly white image is 255, which no pixel is over
+        let mut img = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        img.put_pixel(0, 0, Rgba([0, 0, 0, 255]));
+
+        let braille = BrailleImg::converter()
+            .ditherer(dithering::Bayer4x4)
+            .threshold(dithering::Threshold::Median)
+            .raise(Raise::Light)
+            .convert(img.clone());
+        assert_eq!(braille.get_dot(0, 0), Some(false));
+        assert_eq!(braille.get_dot(1, 1), Some(true));
+        assert_eq!(braille.get_dot(3, 3), Some(true));
+
+        let braille = BrailleImg::converter()
+            .ditherer(dithering::AdaptiveThreshold::new(
+                dithering::AdaptiveMethod::Mean,
+                3,
+            ))
+            .threshold(255)
+            .raise(Raise::Light)
+            .convert(RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255])));
+        assert_eq!(braille.get_dot(2, 2), Some(true));
+    }
+
+    #[test]
+    fn linear_light() {
+        // a ramp of mid grays is much darker in linear light, so fewer light
+        // dots are raised
+        let img = RgbaImage::from_fn(128, 16, |x, _| {
+            let v = 64 + x as u8;
+            Rgba([v, v, v, 255])
+        });
+        let raised = |linear: bool| {
+            let braille = BrailleImg::converter()
+                .ditherer(dithering::FloydSteinberg)
+                .raise(Raise::Light)
+                .linear(linear)
+                .convert(img.clone());
+            (0..16)
+                .flat_map(|y| (0..128).map(move |x| (x, y)))
+                .filter(|&(x, y)| braille.get_dot(x, y) == Some(true))
+                .count()
+        };
+
+        let (srgb, linear) = (raised(false), raised(true));
+        assert!((900..=1150).contains(&srgb), "{srgb} dots in sRGB");
+        assert!(linear < srgb * 2 / 3, "{linear} dots in linear light");
+    }
+
+    #[test]
+    fn background() {
+        let mut img = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 0]));
+        img.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+
+        for (background, raise, raised) in [
+            (Background::Black, Raise::Dark, true),
+            (Background::Black, Raise::Light, false),
+            (Background::White, Raise::Dark, false),
+            (Background::White, Raise::Light, true),
+            (Background::Gray(200), Raise::Dark, false),
+            (Background::NoDot, Raise::Dark, false),
+            (Background::NoDot, Raise::Light, false),
+        ] {
+            let braille = BrailleImg::converter()
+                .ditherer(dithering::None)
+                .background(background)
+                .raise(raise)
+                .convert(img.clone());
+            assert_eq!(braille.get_dot(1, 1), Some(raised), "{background:?}");
+            assert_eq!(
+                braille.get_dot(0, 0),
+                Some(raise == Raise::Light),
+                "{background:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn gray_mode() {
+        // red text on a green background has almost the same luma
+        let mut img = RgbaImage::from_pixel(4, 4, Rgba([0, 120, 0, 255]));
+        img.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+
+        let braille = BrailleImg::converter()
+            .ditherer(dithering::None)
+            .threshold(128)
+            .gray_mode(GrayMode::Channel(Channel::Red))
+            .convert(img.clone());
+        assert_eq!(braille.get_dot(0, 0), Some(false));
+        assert_eq!(braille.get_dot(1, 1), Some(true));
+
+        let braille = BrailleImg::converter()
+            .ditherer(dithering::None)
+            .threshold(128)
+            .gray_mode(GrayMode::Weights([0.0, 1.0, 0.0]))
+            .convert(img);
+        assert_eq!(braille.get_dot(0, 0), Some(true));
+        assert_eq!(braille.get_dot(1, 1), Some(true));
+    }
+
+    #[test]
+    fn cell_colors() {
+        // the left column of the first character is red and raised, the right
+        // one is white and isn't
+        let mut img = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        for y in 0..4 {
+            img.put_pixel(0, y, Rgba([255, 0, 0, 255]));
+        }
+
+        let braille = BrailleImg::converter()
+            .ditherer(dithering::None)
+            .colors(CellColors::ForegroundBackground)
+            .convert(img.clone());
+        assert_eq!(
+            braille.cell_color(0, 0),
+            Some(CellColor {
+                foreground: Some(Rgb::new(255, 0, 0)),
+                background: Some(Rgb::new(255, 255, 255)),
+            })
+        );
+        // nothing is raised in the second character
+        assert_eq!(
+            braille.cell_color(1, 0),
+            Some(CellColor {
+                foreground: Some(Rgb::new(255, 255, 255)),
+                background: Some(Rgb::new(255, 255, 255)),
+            })
+        );
+
+        let braille = BrailleImg::converter().convert(img);
+        assert!(!braille.has_colors());
+    }
+
+    #[test]
+    fn two_color() {
+        // a diagonal blue line on an orange background
+        let img = RgbaImage::from_fn(2, 4, |x, y| {
+            if x == y % 2 {
+                Rgba([0, 0, 200, 255])
+            } else {
+                Rgba([255, 160, 0, 255])
+            }
+        });
+
+        let braille = BrailleImg::converter().convert_two_color(img.clone());
+        for y in 0..4 {
+            for x in 0..2 {
+                assert_eq!(braille.get_dot(x, y), Some(x == y % 2));
+            }
+        }
+        assert_eq!(
+            braille.cell_color(0, 0),
+            Some(CellColor {
+                foreground: Some(Rgb::new(0, 0, 200)),
+                background: Some(Rgb::new(255, 160, 0)),
+            })
+        );
+
+        let inverted = BrailleImg::converter()
+            .raise(Raise::Light)
+            .convert_two_color(img);
+        assert_eq!(inverted.get_dot(0, 0), Some(false));
+        assert_eq!(inverted.get_dot(1, 0), Some(true));
+
+        // flat colors don't raise any dots
+        let flat = RgbaImage::from_pixel(2, 4, Rgba([30, 60, 90, 255]));
+        let braille = BrailleImg::converter().convert_two_color(flat);
+        assert_eq!(braille.as_str(false, true), "⠀");
+    }
+}
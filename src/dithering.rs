@@ -1,123 +1,880 @@
-use std::{ops::Deref, sync::OnceLock};
+use std::{borrow::Cow, ops::Deref, sync::OnceLock};
 
 use image::GrayImage;
 
 pub trait Ditherer {
-    fn dither(&self, buffer: &mut GrayImage);
+    /// turns every pixel of `buffer` either black or white, where pixels over
+    /// `threshold` are generally turned white
+    fn dither(&self, buffer: &mut GrayImage, threshold: u8);
 }
 
 impl<T> Ditherer for T
 where
     T: Deref<Target = dyn Ditherer>,
 {
-    fn dither(&self, buffer: &mut GrayImage) {
-        self.deref().dither(buffer)
+    fn dither(&self, buffer: &mut GrayImage, threshold: u8) {
+        self.deref().dither(buffer, threshold)
     }
 }
 
-fn _srgb_to_linear(val: u8) -> u8 {
-    static LUT: OnceLock<[u8; 256]> = OnceLock::new();
-
-    LUT.get_or_init(|| std::array::from_fn(|i| ((i as f32 / 255.0).powf(1.0 / 2.2) * 255.0) as u8))
-        [val as usize]
+/// how the cutoff between black and white pixels is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Threshold {
+    /// a fixed value, where pixels over it are white
+    Fixed(u8),
+    /// Otsu's method, which picks the value that best separates the histogram
+    /// of the image into two classes
+    Otsu,
+    /// the mean lightness of the image
+    Mean,
+    /// the median lightness of the image
+    Median,
 }
 
-/// the Sierra two-row error-difusion dithering algorithm
-pub struct Sierra2Row;
+impl Threshold {
+    /// the threshold that was used before it was configurable
+    pub const DEFAULT: u8 = 96;
+
+    /// computes the actual threshold for `img`
+    pub fn resolve(&self, img: &GrayImage) -> u8 {
+        let pixels = img.as_raw();
+        if pixels.is_empty() {
+            return match self {
+                Threshold::Fixed(t) => *t,
+                _ => Self::DEFAULT,
+            };
+        }
+
+        match self {
+            Threshold::Fixed(t) => *t,
+            Threshold::Mean => {
+                let sum: u64 = pixels.iter().map(|p| *p as u64).sum();
+                (sum / pixels.len() as u64) as u8
+            }
+            Threshold::Median => {
+                let histogram = histogram(img);
+                let half = pixels.len().div_ceil(2);
+                let mut count = 0;
+                histogram
+                    .iter()
+                    .position(|h| {
+                        count += h;
+                        count >= half
+                    })
+                    .unwrap_or(255) as u8
+            }
+            Threshold::Otsu => {
+                let histogram = histogram(img);
+                let total = pixels.len() as f64;
+                let sum: f64 = histogram
+                    .iter()
+                    .enumerate()
+                    .map(|(i, h)| i as f64 * *h as f64)
+                    .sum();
 
-impl Ditherer for Sierra2Row {
-    fn dither(&self, buffer: &mut GrayImage) {
-        let add_error = |img: &mut image::GrayImage,
-                         x: Option<u32>,
-                         y: Option<u32>,
-                         err: i32,
-                         importance: i32| {
-            if let Some(xpos) = x {
-                if let Some(ypos) = y {
-                    if let Some(pix) = img.get_pixel_mut_checked(xpos, ypos) {
-                        *pix =
-                            image::Luma([(pix.0[0] as i32 + err * importance).clamp(0, 255) as u8]);
+                let mut best = (0, 0.0);
+                let mut background_weight = 0.0;
+                let mut background_sum = 0.0;
+                for (t, h) in histogram.iter().enumerate() {
+                    background_weight += *h as f64;
+                    background_sum += t as f64 * *h as f64;
+                    let foreground_weight = total - background_weight;
+                    if background_weight == 0.0 {
+                        continue;
+                    }
+                    if foreground_weight == 0.0 {
+                        break;
+                    }
+                    let background_mean = background_sum / background_weight;
+                    let foreground_mean = (sum - background_sum) / foreground_weight;
+                    let variance = background_weight
+                        * foreground_weight
+                        * (background_mean - foreground_mean).powi(2);
+                    if variance > best.1 {
+                        best = (t, variance);
                     }
                 }
+                best.0 as u8
             }
-        };
+        }
+    }
+}
+
+impl Default for Threshold {
+    fn default() -> Self {
+        Threshold::Fixed(Self::DEFAULT)
+    }
+}
+
+impl From<u8> for Threshold {
+    fn from(value: u8) -> Self {
+        Threshold::Fixed(value)
+    }
+}
+
+fn histogram(img: &GrayImage) -> [usize; 256] {
+    let mut histogram = [0; 256];
+    for pix in img.as_raw() {
+        histogram[*pix as usize] += 1;
+    }
+    histogram
+}
+
+/// applies an ordered dithering matrix, with the pixels shifted by how far
+/// `threshold` is from [Threshold::DEFAULT], so the matrix is used as it is
+/// with the default threshold, where the density of white pixels matches the
+/// lightness of the image
+fn ordered_dither(buffer: &mut GrayImage, threshold: u8, matrix: impl Fn(u32, u32) -> u8) {
+    let bias = Threshold::DEFAULT as i32 - threshold as i32;
+    for (x, y, pix) in buffer.enumerate_pixels_mut() {
+        if pix.0[0] as i32 + bias > matrix(x, y) as i32 {
+            pix.0[0] = 255;
+        } else {
+            pix.0[0] = 0;
+        }
+    }
+}
+
+/// a matrix describing how the quantization error of a pixel is spread over
+/// its not yet processed neighbours.
+///
+/// each entry is `(dx, dy, weight)`, relative to the current pixel, and the
+/// error each neighbour receives is `error * weight / divisor`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffusionKernel {
+    weights: Cow<'static, [(i32, u32, u32)]>,
+    divisor: u32,
+}
+
+impl DiffusionKernel {
+    /// the Floyd–Steinberg kernel
+    #[rustfmt::skip]
+    pub const FLOYD_STEINBERG: Self = Self::from_static(&[
+                          (1, 0, 7),
+        (-1, 1, 3), (0, 1, 5), (1, 1, 1),
+    ], 16);
+
+    /// the Atkinson kernel, which only diffuses 3/4 of the error
+    #[rustfmt::skip]
+    pub const ATKINSON: Self = Self::from_static(&[
+                          (1, 0, 1), (2, 0, 1),
+        (-1, 1, 1), (0, 1, 1), (1, 1, 1),
+                    (0, 2, 1),
+    ], 8);
+
+    /// the Jarvis, Judice and Ninke kernel
+    #[rustfmt::skip]
+    pub const JARVIS_JUDICE_NINKE: Self = Self::from_static(&[
+                                      (1, 0, 7), (2, 0, 5),
+        (-2, 1, 3), (-1, 1, 5), (0, 1, 7), (1, 1, 5), (2, 1, 3),
+        (-2, 2, 1), (-1, 2, 3), (0, 2, 5), (1, 2, 3), (2, 2, 1),
+    ], 48);
+
+    /// the Stucki kernel
+    #[rustfmt::skip]
+    pub const STUCKI: Self = Self::from_static(&[
+                                      (1, 0, 8), (2, 0, 4),
+        (-2, 1, 2), (-1, 1, 4), (0, 1, 8), (1, 1, 4), (2, 1, 2),
+        (-2, 2, 1), (-1, 2, 2), (0, 2, 4), (1, 2, 2), (2, 2, 1),
+    ], 42);
+
+    /// the Burkes kernel
+    #[rustfmt::skip]
+    pub const BURKES: Self = Self::from_static(&[
+                                      (1, 0, 8), (2, 0, 4),
+        (-2, 1, 2), (-1, 1, 4), (0, 1, 8), (1, 1, 4), (2, 1, 2),
+    ], 32);
 
-        for y in 0..buffer.height() {
-            for x in 0..buffer.width() {
-                let cur_pix = buffer.get_pixel_mut(x, y);
-                let error = if cur_pix.0[0] > 96 {
-                    cur_pix.0[0] as i32 - 255
-                } else {
-                    cur_pix.0[0] as i32
-                } >> 5;
-                if cur_pix.0[0] > 96 {
-                    cur_pix.0[0] = 255;
-                } else {
-                    cur_pix.0[0] = 0;
+    /// the Sierra (three-row) kernel
+    #[rustfmt::skip]
+    pub const SIERRA3: Self = Self::from_static(&[
+                                      (1, 0, 5), (2, 0, 3),
+        (-2, 1, 2), (-1, 1, 4), (0, 1, 5), (1, 1, 4), (2, 1, 2),
+                    (-1, 2, 2), (0, 2, 3), (1, 2, 2),
+    ], 32);
+
+    /// the Sierra two-row kernel
+    #[rustfmt::skip]
+    pub const SIERRA2: Self = Self::from_static(&[
+                                      (1, 0, 4), (2, 0, 3),
+        (-2, 1, 1), (-1, 1, 2), (0, 1, 3), (1, 1, 2), (2, 1, 1),
+    ], 16);
+
+    /// the Sierra Lite kernel
+    #[rustfmt::skip]
+    pub const SIERRA_LITE: Self = Self::from_static(&[
+                    (1, 0, 2),
+        (-1, 1, 1), (0, 1, 1),
+    ], 4);
+
+    const fn from_static(weights: &'static [(i32, u32, u32)], divisor: u32) -> Self {
+        Self {
+            weights: Cow::Borrowed(weights),
+            divisor,
+        }
+    }
+
+    /// creates a custom kernel out of `(dx, dy, weight)` entries
+    /// # Panics
+    /// if `divisor` is 0 or if any entry points at the current pixel or one
+    /// that was already processed, that is, `dy == 0 && dx <= 0`
+    pub fn new(weights: impl Into<Cow<'static, [(i32, u32, u32)]>>, divisor: u32) -> Self {
+        let weights = weights.into();
+        assert!(divisor != 0, "divisor must be greater than 0");
+        assert!(
+            weights.iter().all(|&(dx, dy, _)| dy > 0 || dx > 0),
+            "error can only be diffused to pixels that weren't processed yet"
+        );
+        Self { weights, divisor }
+    }
+
+    /// the `(dx, dy, weight)` entries of this kernel
+    pub fn weights(&self) -> &[(i32, u32, u32)] {
+        &self.weights
+    }
+
+    pub fn divisor(&self) -> u32 {
+        self.divisor
+    }
+}
+
+/// a generic error-diffusion ditherer using any [DiffusionKernel]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorDiffusion {
+    kernel: DiffusionKernel,
+    serpentine: bool,
+}
+
+impl ErrorDiffusion {
+    pub fn new(kernel: DiffusionKernel) -> Self {
+        Self {
+            kernel,
+            serpentine: false,
+        }
+    }
+
+    /// if true, every other row is scanned right to left with the kernel
+    /// mirrored, which avoids the diagonal artifacts caused by always
+    /// diffusing the error in the same direction
+    pub fn serpentine(mut self, serpentine: bool) -> Self {
+        self.serpentine = serpentine;
+        self
+    }
+
+    pub fn is_serpentine(&self) -> bool {
+        self.serpentine
+    }
+
+    pub fn kernel(&self) -> &DiffusionKernel {
+        &self.kernel
+    }
+}
+
+impl Ditherer for ErrorDiffusion {
+    fn dither(&self, buffer: &mut GrayImage, threshold: u8) {
+        let (width, height) = (buffer.width() as usize, buffer.height() as usize);
+        let divisor = self.kernel.divisor as i32;
+        // errors are accumulated with a wider type so they don't get clamped
+        // before being propagated further
+        let mut values: Vec<i32> = buffer.as_raw().iter().map(|v| *v as i32).collect();
+
+        for y in 0..height {
+            let reversed = self.serpentine && y % 2 == 1;
+            for i in 0..width {
+                let x = if reversed { width - 1 - i } else { i };
+                let idx = x + y * width;
+                let old = values[idx];
+                let new = if old > threshold as i32 { 255 } else { 0 };
+                let error = old - new;
+                buffer.as_mut()[idx] = new as u8;
+
+                for &(dx, dy, weight) in self.kernel.weights.iter() {
+                    let dx = if reversed { -dx } else { dx };
+                    let Some(nx) = x.checked_add_signed(dx as isize) else {
+                        continue;
+                    };
+                    let ny = y + dy as usize;
+                    if nx >= width || ny >= height {
+                        continue;
+                    }
+                    values[nx + ny * width] += error * weight as i32 / divisor;
                 }
+            }
+        }
+    }
+}
 
-                add_error(buffer, x.checked_add(1), Some(y), error, 5);
-                add_error(buffer, x.checked_add(2), Some(y), error, 3);
-                add_error(buffer, x.checked_sub(2), Some(y + 1), error, 2);
-                add_error(buffer, x.checked_sub(1), Some(y + 1), error, 4);
-                add_error(buffer, Some(x), Some(y + 1), error, 5);
-                add_error(buffer, x.checked_add(1), Some(y + 1), error, 4);
-                add_error(buffer, x.checked_add(2), Some(y + 1), error, 2);
-                add_error(buffer, x.checked_sub(1), Some(y + 2), error, 2);
-                add_error(buffer, Some(x), Some(y + 2), error, 3);
-                add_error(buffer, x.checked_add(1), Some(y + 2), error, 2);
+macro_rules! error_diffusion_ditherer {
+    ($(#[$attr:meta])* $name:ident => $kernel:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct $name;
+
+        impl $name {
+            /// turns this into an [ErrorDiffusion] ditherer with the given
+            /// serpentine scanning setting, see [ErrorDiffusion::serpentine]
+            pub fn serpentine(self, serpentine: bool) -> ErrorDiffusion {
+                ErrorDiffusion::from(self).serpentine(serpentine)
+            }
+        }
+
+        impl Ditherer for $name {
+            fn dither(&self, buffer: &mut GrayImage, threshold: u8) {
+                ErrorDiffusion::from(*self).dither(buffer, threshold)
             }
         }
+
+        impl From<$name> for ErrorDiffusion {
+            fn from(_: $name) -> Self {
+                ErrorDiffusion::new(DiffusionKernel::$kernel)
+            }
+        }
+    };
+}
+
+error_diffusion_ditherer! {
+    /// the Floyd–Steinberg error-diffusion dithering algorithm
+    FloydSteinberg => FLOYD_STEINBERG
+}
+
+error_diffusion_ditherer! {
+    /// the Atkinson error-diffusion dithering algorithm
+    Atkinson => ATKINSON
+}
+
+error_diffusion_ditherer! {
+    /// the Jarvis, Judice and Ninke error-diffusion dithering algorithm
+    JarvisJudiceNinke => JARVIS_JUDICE_NINKE
+}
+
+error_diffusion_ditherer! {
+    /// the Stucki error-diffusion dithering algorithm
+    Stucki => STUCKI
+}
+
+error_diffusion_ditherer! {
+    /// the Burkes error-diffusion dithering algorithm
+    Burkes => BURKES
+}
+
+error_diffusion_ditherer! {
+    /// the Sierra (three-row) error-diffusion dithering algorithm
+    Sierra3 => SIERRA3
+}
+
+error_diffusion_ditherer! {
+    /// the Sierra error-diffusion dithering algorithm, which has always used
+    /// the three-row kernel despite its name, so it is the same as [Sierra3].
+    /// see [Sierra2] for the two-row kernel
+    Sierra2Row => SIERRA3
+}
+
+error_diffusion_ditherer! {
+    /// the Sierra two-row error-diffusion dithering algorithm
+    Sierra2 => SIERRA2
+}
+
+error_diffusion_ditherer! {
+    /// the Sierra Lite error-diffusion dithering algorithm
+    SierraLite => SIERRA_LITE
+}
+
+/// the Bayer ordered dithering algorithm, with a `2^order` by `2^order`
+/// threshold matrix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bayer {
+    order: u8,
+}
+
+impl Bayer {
+    /// creates a Bayer ditherer with a `2^order` by `2^order` matrix, so an
+    /// order of 3 results in an 8x8 matrix
+    /// # Panics
+    /// if `order` is 0 or greater than 8, since there can't be more than 256
+    /// distinct thresholds for 8-bit pixels anyway
+    pub const fn new(order: u8) -> Self {
+        assert!(order > 0 && order <= 8, "order must be between 1 and 8");
+        Self { order }
+    }
+
+    pub const fn order(&self) -> u8 {
+        self.order
+    }
+
+    /// the width and height of the threshold matrix
+    pub const fn size(&self) -> u32 {
+        1 << self.order
+    }
+
+    /// the threshold at `x` and `y`, which wrap around the matrix
+    pub const fn threshold(&self, x: u32, y: u32) -> u8 {
+        // the matrix of order n + 1 is made out of four copies of the matrix
+        // of order n, multiplied by 4 and offset by [[0, 2], [3, 1]], so the
+        // least significant bits of the coordinates pick the most significant
+        // digits of the index
+        const OFFSETS: [[u32; 2]; 2] = [[0, 2], [3, 1]];
+        let mut index = 0;
+        let mut bit = 0;
+        while bit < self.order {
+            let offset = OFFSETS[((y >> bit) & 1) as usize][((x >> bit) & 1) as usize];
+            index = index * 4 + offset;
+            bit += 1;
+        }
+        // scales the index from 0..4^order to 0..256
+        ((index << 8) >> (2 * self.order as u32)) as u8
     }
 }
 
-/// the Bayer ordered dithering algorithm, with a 4x4 matrix
+impl Ditherer for Bayer {
+    fn dither(&self, buffer: &mut GrayImage, threshold: u8) {
+        ordered_dither(buffer, threshold, |x, y| self.threshold(x, y))
+    }
+}
+
+/// the Bayer ordered dithering algorithm, with a 4x4 matrix, same as
+/// `Bayer::new(2)`
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Bayer4x4;
 
-#[rustfmt::skip]
-const BAYER4X4_MATRIX: [[u8; 4]; 4] = [
-    [0  , 128, 32 , 160],
-    [192, 64 , 224, 96 ],
-    [48 , 176, 16 , 144],
-    [240, 112, 208, 80 ]
-];
+impl From<Bayer4x4> for Bayer {
+    fn from(_: Bayer4x4) -> Self {
+        Bayer::new(2)
+    }
+}
 
 impl Ditherer for Bayer4x4 {
-    fn dither(&self, buffer: &mut GrayImage) {
-        for (x, y, pix) in buffer.enumerate_pixels_mut() {
-            if pix.0[0] > BAYER4X4_MATRIX[(y % 4) as usize][(x % 4) as usize] {
-                pix.0[0] = 255;
-            } else {
-                pix.0[0] = 0;
-            }
-        }
+    fn dither(&self, buffer: &mut GrayImage, threshold: u8) {
+        Bayer::from(*self).dither(buffer, threshold)
     }
 }
 
-/// the Bayer ordered dithering algorithm, with a 4x4 matrix
+/// the Bayer ordered dithering algorithm, with a 2x2 matrix, same as
+/// `Bayer::new(1)`
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Bayer2x2;
 
-const BAYER2X2_MATRIX: [[u8; 2]; 2] = [[0, 128], [192, 64]];
+impl From<Bayer2x2> for Bayer {
+    fn from(_: Bayer2x2) -> Self {
+        Bayer::new(1)
+    }
+}
 
 impl Ditherer for Bayer2x2 {
-    fn dither(&self, buffer: &mut GrayImage) {
-        for (x, y, pix) in buffer.enumerate_pixels_mut() {
-            if pix.0[0] > BAYER2X2_MATRIX[(y % 2) as usize][(x % 2) as usize] {
-                pix.0[0] = 255;
-            } else {
-                pix.0[0] = 0;
+    fn dither(&self, buffer: &mut GrayImage, threshold: u8) {
+        Bayer::from(*self).dither(buffer, threshold)
+    }
+}
+
+/// an ordered ditherer using an arbitrary threshold mask, which is tiled over
+/// the whole image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdMask {
+    thresholds: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+impl ThresholdMask {
+    /// creates a mask out of `thresholds` in row-major order
+    /// # Panics
+    /// if either width or height is 0, or if `thresholds` doesn't have exactly
+    /// `width * height` values
+    pub fn new(width: u32, height: u32, thresholds: Vec<u8>) -> Self {
+        assert!(
+            width != 0 && height != 0,
+            "width and height must be greater than 0"
+        );
+        assert_eq!(
+            thresholds.len(),
+            width as usize * height as usize,
+            "thresholds must have width * height values"
+        );
+        Self {
+            thresholds,
+            width,
+            height,
+        }
+    }
+
+    /// uses the pixel values of a grayscale image directly as thresholds,
+    /// such as the blue noise textures that are commonly distributed as PNGs
+    /// # Panics
+    /// if the image is empty
+    pub fn from_image(img: &GrayImage) -> Self {
+        Self::new(img.width(), img.height(), img.as_raw().clone())
+    }
+
+    /// generates a `size` by `size` blue noise mask with the void-and-cluster
+    /// method, the same `seed` always results in the same mask
+    /// # Panics
+    /// if `size` is 0
+    pub fn blue_noise(size: u32, seed: u64) -> Self {
+        assert!(size != 0, "size must be greater than 0");
+        let ranks = VoidAndCluster::new(size, seed).ranks();
+        let len = ranks.len() as u64;
+        let thresholds = ranks
+            .into_iter()
+            .map(|r| (r as u64 * 256 / len) as u8)
+            .collect();
+        Self::new(size, size, thresholds)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// the threshold at `x` and `y`, which wrap around the mask
+    pub fn threshold(&self, x: u32, y: u32) -> u8 {
+        self.thresholds[((x % self.width) + (y % self.height) * self.width) as usize]
+    }
+}
+
+impl Ditherer for ThresholdMask {
+    fn dither(&self, buffer: &mut GrayImage, threshold: u8) {
+        ordered_dither(buffer, threshold, |x, y| self.threshold(x, y))
+    }
+}
+
+/// ordered dithering with a 64x64 blue noise mask, which avoids the grid-like
+/// look of [Bayer] dithering
+///
+/// the mask is generated once with [ThresholdMask::blue_noise] and reused, use
+/// that directly for other sizes or seeds
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlueNoise;
+
+impl BlueNoise {
+    const SIZE: u32 = 64;
+    const SEED: u64 = 0;
+
+    pub fn mask() -> &'static ThresholdMask {
+        static MASK: OnceLock<ThresholdMask> = OnceLock::new();
+
+        MASK.get_or_init(|| ThresholdMask::blue_noise(Self::SIZE, Self::SEED))
+    }
+}
+
+impl Ditherer for BlueNoise {
+    fn dither(&self, buffer: &mut GrayImage, threshold: u8) {
+        Self::mask().dither(buffer, threshold)
+    }
+}
+
+/// state for generating a blue noise mask with Ulichney's void-and-cluster
+/// method, on a toroidal grid
+struct VoidAndCluster {
+    size: usize,
+    /// gaussian weights indexed by the wrapped distance between two points
+    weights: Vec<f32>,
+    pattern: Vec<bool>,
+    energy: Vec<f32>,
+}
+
+impl VoidAndCluster {
+    const SIGMA: f32 = 1.5;
+
+    fn new(size: u32, seed: u64) -> Self {
+        let size = size as usize;
+        let len = size * size;
+
+        let weights = (0..len)
+            .map(|i| {
+                let dx = (i % size).min(size - i % size) as f32;
+                let dy = (i / size).min(size - i / size) as f32;
+                (-(dx * dx + dy * dy) / (2.0 * Self::SIGMA * Self::SIGMA)).exp()
+            })
+            .collect();
+
+        let mut state = Self {
+            size,
+            weights,
+            pattern: vec![false; len],
+            energy: vec![0.0; len],
+        };
+
+        // splitmix64, so the masks don't depend on any external RNG
+        let mut seed = seed;
+        let mut next_random = move || {
+            seed = seed.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+
+        let initial = (len / 10).max(1);
+        let mut placed = 0;
+        while placed < initial {
+            let idx = (next_random() % len as u64) as usize;
+            if !state.pattern[idx] {
+                state.set(idx, true);
+                placed += 1;
+            }
+        }
+
+        state
+    }
+
+    fn set(&mut self, idx: usize, value: bool) {
+        self.pattern[idx] = value;
+        let sign = if value { 1.0 } else { -1.0 };
+        let size = self.size;
+        let (ix, iy) = (idx % size, idx / size);
+        for (jy, row) in self.energy.chunks_exact_mut(size).enumerate() {
+            let dy = (jy + size - iy) % size;
+            let weights = &self.weights[dy * size..(dy + 1) * size];
+            // the weights of the row are rotated so that column ix lines up
+            // with a distance of 0
+            let (left, right) = weights.split_at(size - ix);
+            for (energy, weight) in row.iter_mut().zip(right.iter().chain(left)) {
+                *energy += sign * weight;
             }
         }
     }
+
+    fn tightest_cluster(&self) -> usize {
+        self.pattern
+            .iter()
+            .zip(&self.energy)
+            .enumerate()
+            .filter(|(_, (p, _))| **p)
+            .fold(
+                (0, f32::MIN),
+                |best, (i, (_, e))| {
+                    if *e > best.1 {
+                        (i, *e)
+                    } else {
+                        best
+                    }
+                },
+            )
+            .0
+    }
+
+    fn largest_void(&self) -> usize {
+        self.pattern
+            .iter()
+            .zip(&self.energy)
+            .enumerate()
+            .filter(|(_, (p, _))| !**p)
+            .fold(
+                (0, f32::MAX),
+                |best, (i, (_, e))| {
+                    if *e < best.1 {
+                        (i, *e)
+                    } else {
+                        best
+                    }
+                },
+            )
+            .0
+    }
+
+    /// the rank of every point, from 0 to `size * size - 1`
+    fn ranks(mut self) -> Vec<u32> {
+        let len = self.pattern.len();
+
+        // spreads the initial points out until moving the tightest cluster
+        // into the largest void doesn't change anything anymore
+        for _ in 0..len {
+            let cluster = self.tightest_cluster();
+            self.set(cluster, false);
+            let void = self.largest_void();
+            self.set(void, true);
+            if void == cluster {
+                break;
+            }
+        }
+
+        let mut ranks = vec![0; len];
+        let initial_pattern = self.pattern.clone();
+        let initial_energy = self.energy.clone();
+        let mut ones = initial_pattern.iter().filter(|p| **p).count();
+
+        // removes the initial points from the most to the least clustered
+        let mut rank = ones;
+        while rank > 0 {
+            let cluster = self.tightest_cluster();
+            self.set(cluster, false);
+            rank -= 1;
+            ranks[cluster] = rank as u32;
+        }
+
+        // then fills up the rest from the largest to the smallest void
+        self.pattern = initial_pattern;
+        self.energy = initial_energy;
+        while ones < len {
+            let void = self.largest_void();
+            self.set(void, true);
+            ranks[void] = ones as u32;
+            ones += 1;
+        }
+
+        ranks
+    }
+}
+
+/// how [AdaptiveThreshold] computes the threshold of each pixel from its
+/// neighbourhood
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdaptiveMethod {
+    /// the mean of the window minus the bias
+    Mean,
+    /// a gaussian-weighted mean of the window minus the bias, approximated by
+    /// three passes of a box filter
+    Gaussian,
+    /// Niblack's method, the mean of the window plus the bias times its
+    /// standard deviation
+    Niblack,
+    /// Sauvola's method, `mean * (1 + bias * (deviation / 128 - 1))`, which
+    /// handles flat backgrounds better than Niblack's
+    Sauvola,
+}
+
+impl AdaptiveMethod {
+    /// a sensible bias for each method
+    pub fn default_bias(&self) -> f32 {
+        match self {
+            AdaptiveMethod::Mean | AdaptiveMethod::Gaussian => 5.0,
+            AdaptiveMethod::Niblack => -0.2,
+            AdaptiveMethod::Sauvola => 0.34,
+        }
+    }
+}
+
+/// locally adaptive thresholding, where each pixel is compared against a
+/// threshold computed from the window around it, useful for images with
+/// uneven lighting such as scanned documents
+///
+/// the global threshold passed to [Ditherer::dither] is ignored
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveThreshold {
+    method: AdaptiveMethod,
+    window: u32,
+    bias: f32,
 }
 
-/// No dithering, raises dots over 96
+impl AdaptiveThreshold {
+    /// creates an adaptive ditherer with a `window` by `window` neighbourhood
+    /// and the method's default bias
+    /// # Panics
+    /// if `window` is 0
+    pub fn new(method: AdaptiveMethod, window: u32) -> Self {
+        assert!(window != 0, "window must be greater than 0");
+        Self {
+            method,
+            window,
+            bias: method.default_bias(),
+        }
+    }
+
+    /// sets the bias, see [AdaptiveMethod] for what it means for each method
+    pub fn bias(mut self, bias: f32) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    pub fn method(&self) -> AdaptiveMethod {
+        self.method
+    }
+
+    pub fn window(&self) -> u32 {
+        self.window
+    }
+}
+
+impl Ditherer for AdaptiveThreshold {
+    fn dither(&self, buffer: &mut GrayImage, _threshold: u8) {
+        let (width, height) = (buffer.width() as usize, buffer.height() as usize);
+        let radius = self.window as usize / 2;
+        let bias = self.bias as f64;
+        let values: Vec<f64> = buffer.as_raw().iter().map(|v| *v as f64).collect();
+
+        let thresholds: Vec<f64> = match self.method {
+            AdaptiveMethod::Mean => box_blur(&values, width, height, radius)
+                .into_iter()
+                .map(|mean| mean - bias)
+                .collect(),
+            AdaptiveMethod::Gaussian => {
+                // three box passes of width w have a variance of (w² - 1) / 4,
+                // which is matched against a gaussian spanning the window. small
+                // windows would round to boxes of a single pixel, which don't
+                // blur at all, so they are at least 3 pixels wide
+                let sigma = self.window as f64 / 6.0;
+                let box_radius = (((4.0 * sigma * sigma + 1.0).sqrt() - 1.0) / 2.0).round();
+                let box_radius = (box_radius as usize).max(1);
+                let mut blurred = values.clone();
+                for _ in 0..3 {
+                    blurred = box_blur(&blurred, width, height, box_radius);
+                }
+                blurred.into_iter().map(|mean| mean - bias).collect()
+            }
+            AdaptiveMethod::Niblack | AdaptiveMethod::Sauvola => {
+                let squares: Vec<f64> = values.iter().map(|v| v * v).collect();
+                let means = box_blur(&values, width, height, radius);
+                let square_means = box_blur(&squares, width, height, radius);
+                means
+                    .into_iter()
+                    .zip(square_means)
+                    .map(|(mean, square_mean)| {
+                        let deviation = (square_mean - mean * mean).max(0.0).sqrt();
+                        if self.method == AdaptiveMethod::Niblack {
+                            mean + bias * deviation
+                        } else {
+                            mean * (1.0 + bias * (deviation / 128.0 - 1.0))
+                        }
+                    })
+                    .collect()
+            }
+        };
+
+        for (pix, threshold) in buffer.iter_mut().zip(thresholds) {
+            *pix = if *pix as f64 > threshold { 255 } else { 0 };
+        }
+    }
+}
+
+/// the mean of the `2 * radius + 1` square window around every value, clipped
+/// to the edges of the image, computed with an integral image
+fn box_blur(values: &[f64], width: usize, height: usize, radius: usize) -> Vec<f64> {
+    // has an extra row and column of zeroes at the top and left, so that
+    // every window sum is just 4 lookups
+    let stride = width + 1;
+    let mut integral = vec![0.0; stride * (height + 1)];
+    for y in 0..height {
+        let mut row_sum = 0.0;
+        for x in 0..width {
+            row_sum += values[x + y * width];
+            integral[(x + 1) + (y + 1) * stride] = integral[(x + 1) + y * stride] + row_sum;
+        }
+    }
+
+    let mut blurred = Vec::with_capacity(values.len());
+    for y in 0..height {
+        let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+        for x in 0..width {
+            let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+            let sum = integral[right + bottom * stride]
+                - integral[left + bottom * stride]
+                - integral[right + top * stride]
+                + integral[left + top * stride];
+            blurred.push(sum / ((right - left) * (bottom - top)) as f64);
+        }
+    }
+    blurred
+}
+
+/// No dithering, just turns pixels over the threshold white
 pub struct None;
 
 impl Ditherer for None {
-    fn dither(&self, buffer: &mut GrayImage) {
+    fn dither(&self, buffer: &mut GrayImage, threshold: u8) {
         for pix in buffer.pixels_mut() {
-            if pix.0[0] > 96 {
+            if pix.0[0] > threshold {
                 pix.0[0] = 255;
             } else {
                 pix.0[0] = 0;
@@ -125,3 +882,174 @@ impl Ditherer for None {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use image::GrayImage;
+
+    use super::{
+        AdaptiveMethod, AdaptiveThreshold, Bayer, DiffusionKernel, Ditherer, ErrorDiffusion,
+        Sierra2, Sierra2Row, Sierra3, Threshold, ThresholdMask,
+    };
+
+    #[test]
+    fn kernels_are_normalized() {
+        for kernel in [
+            DiffusionKernel::FLOYD_STEINBERG,
+            DiffusionKernel::JARVIS_JUDICE_NINKE,
+            DiffusionKernel::STUCKI,
+            DiffusionKernel::BURKES,
+            DiffusionKernel::SIERRA3,
+            DiffusionKernel::SIERRA2,
+            DiffusionKernel::SIERRA_LITE,
+        ] {
+            let sum: u32 = kernel.weights().iter().map(|w| w.2).sum();
+            assert_eq!(sum, kernel.divisor());
+        }
+    }
+
+    #[test]
+    fn sierra_kernels() {
+        // Sierra2Row keeps the three-row kernel it has always used
+        let kernel = |ditherer: ErrorDiffusion| ditherer.kernel().clone();
+        assert_eq!(kernel(Sierra2Row.into()), DiffusionKernel::SIERRA3);
+        assert_eq!(kernel(Sierra3.into()), DiffusionKernel::SIERRA3);
+        assert_eq!(kernel(Sierra2.into()), DiffusionKernel::SIERRA2);
+    }
+
+    #[test]
+    fn bayer_matrices() {
+        #[rustfmt::skip]
+        const BAYER4X4_MATRIX: [[u8; 4]; 4] = [
+            [0  , 128, 32 , 160],
+            [192, 64 , 224, 96 ],
+            [48 , 176, 16 , 144],
+            [240, 112, 208, 80 ]
+        ];
+        const BAYER2X2_MATRIX: [[u8; 2]; 2] = [[0, 128], [192, 64]];
+
+        let bayer = Bayer::new(2);
+        for (y, row) in BAYER4X4_MATRIX.iter().enumerate() {
+            for (x, val) in row.iter().enumerate() {
+                assert_eq!(bayer.threshold(x as u32, y as u32), *val);
+            }
+        }
+        let bayer = Bayer::new(1);
+        for (y, row) in BAYER2X2_MATRIX.iter().enumerate() {
+            for (x, val) in row.iter().enumerate() {
+                assert_eq!(bayer.threshold(x as u32, y as u32), *val);
+            }
+        }
+
+        // every threshold of a 16x16 matrix should be unique
+        let bayer = Bayer::new(4);
+        let mut seen = [false; 256];
+        for y in 0..16 {
+            for x in 0..16 {
+                let t = bayer.threshold(x, y) as usize;
+                assert!(!seen[t]);
+                seen[t] = true;
+            }
+        }
+    }
+
+    #[test]
+    fn blue_noise_mask() {
+        let mask = ThresholdMask::blue_noise(16, 42);
+        assert_eq!(mask, ThresholdMask::blue_noise(16, 42));
+
+        // every rank is used exactly once
+        let mut seen = [false; 256];
+        for y in 0..16 {
+            for x in 0..16 {
+                let t = mask.threshold(x, y) as usize;
+                assert!(!seen[t]);
+                seen[t] = true;
+            }
+        }
+    }
+
+    #[test]
+    fn automatic_thresholds() {
+        // two clearly separated groups of values
+        let mut pixels = vec![20; 60];
+        pixels.extend([200; 40]);
+        let img = GrayImage::from_raw(10, 10, pixels).unwrap();
+
+        let otsu = Threshold::Otsu.resolve(&img);
+        assert!((20..200).contains(&otsu), "otsu threshold was {otsu}");
+        assert_eq!(Threshold::Mean.resolve(&img), 92);
+        assert_eq!(Threshold::Median.resolve(&img), 20);
+        assert_eq!(Threshold::Fixed(7).resolve(&img), 7);
+    }
+
+    #[test]
+    fn adaptive_uneven_lighting() {
+        // a dark stripe on a background that gets brighter to the right
+        let img = GrayImage::from_fn(64, 8, |x, y| {
+            let background = 120 + x as u8 * 2;
+            image::Luma([if y == 4 { background / 3 } else { background }])
+        });
+
+        for method in [
+            AdaptiveMethod::Mean,
+            AdaptiveMethod::Gaussian,
+            AdaptiveMethod::Sauvola,
+        ] {
+            let mut img = img.clone();
+            AdaptiveThreshold::new(method, 7).dither(&mut img, Threshold::DEFAULT);
+            for x in 4..60 {
+                assert_eq!(img.get_pixel(x, 4).0[0], 0, "{method:?} at {x}");
+                assert_eq!(img.get_pixel(x, 1).0[0], 255, "{method:?} at {x}");
+            }
+        }
+    }
+
+    #[test]
+    fn adaptive_small_gaussian_window() {
+        // dark and light halves, where the pixels on either side of the edge
+        // should end up on their own side
+        let img = GrayImage::from_fn(32, 32, |x, _| image::Luma([if x < 16 { 40 } else { 200 }]));
+        for window in [1, 3, 5] {
+            let mut img = img.clone();
+            AdaptiveThreshold::new(AdaptiveMethod::Gaussian, window)
+                .dither(&mut img, Threshold::DEFAULT);
+            for y in 0..32 {
+                assert_eq!(img.get_pixel(15, y).0[0], 0, "window {window}");
+                assert_eq!(img.get_pixel(16, y).0[0], 255, "window {window}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn kernel_backwards_diffusion() {
+        let _kernel = DiffusionKernel::new(vec![(-1, 0, 1), (0, 1, 1)], 2);
+    }
+
+    #[test]
+    fn error_diffusion_preserves_average() {
+        let mut img = GrayImage::from_pixel(32, 32, image::Luma([128]));
+        ErrorDiffusion::new(DiffusionKernel::FLOYD_STEINBERG).dither(&mut img, Threshold::DEFAULT);
+        let raised = img.pixels().filter(|p| p.0[0] == 255).count();
+        // roughly half of the pixels should end up white
+        assert!((400..=624).contains(&raised), "{raised} white pixels");
+    }
+
+    #[test]
+    fn serpentine_mirrors_kernel() {
+        // the second row is scanned right to left, so its error should be
+        // pushed to the left instead
+        let row = vec![0, 0, 0, 90, 60, 0];
+
+        let mut img = GrayImage::from_raw(3, 2, row.clone()).unwrap();
+        ErrorDiffusion::new(DiffusionKernel::SIERRA_LITE).dither(&mut img, Threshold::DEFAULT);
+        assert_eq!(&img.as_raw()[3..], &[0, 255, 0]);
+
+        let mut img = GrayImage::from_raw(3, 2, row).unwrap();
+        ErrorDiffusion::new(DiffusionKernel::SIERRA_LITE)
+            .serpentine(true)
+            .dither(&mut img, Threshold::DEFAULT);
+        assert_eq!(&img.as_raw()[3..], &[255, 0, 0]);
+    }
+}
@@ -16,10 +16,24 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
 pub mod braille;
+pub mod color;
+pub mod plot;
+pub mod text;
 
+#[cfg(feature = "image")]
+pub mod convert;
 #[cfg(feature = "image")]
 pub mod dithering;
 
-pub use braille::{BrailleImg, Error};
+pub use braille::{BlitOp, BrailleImg, Error, ResizeMethod, SvgOptions};
+#[cfg(feature = "image")]
+pub use braille::{Coverage, RenderOptions};
+pub use color::{CellColor, ColorMode, Rgb};
+#[cfg(feature = "image")]
+pub use convert::{Converter, Raise};
 #[cfg(feature = "image")]
-pub use dithering::{Bayer2x2, Bayer4x4, None, Sierra2Row};
+pub use dithering::{
+    AdaptiveMethod, AdaptiveThreshold, Atkinson, Bayer, Bayer2x2, Bayer4x4, BlueNoise, Burkes,
+    FloydSteinberg, JarvisJudiceNinke, None, Sierra2, Sierra2Row, Sierra3, SierraLite, Stucki,
+    Threshold, ThresholdMask,
+};
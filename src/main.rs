@@ -2,7 +2,7 @@
 
 use std::{
     fs::read,
-    io::{stdin, Read},
+    io::{stdin, stdout, Cursor, Read, Write},
     path::PathBuf,
     time::Instant,
 };
@@ -11,8 +11,11 @@ use clap::{CommandFactory, Parser};
 use clap_complete::generate;
 use image::GenericImageView;
 use lib::{
-    braille,
-    dithering::{self, Ditherer},
+    braille::{self, BrailleImg, RenderOptions, SvgOptions},
+    color::{ColorMode, Rgb},
+    convert::{CellColors, Raise},
+    dithering::{self, AdaptiveMethod, AdaptiveThreshold, Ditherer},
+    text::Font,
 };
 use log::{debug, error};
 use make_it_braille as lib;
@@ -20,7 +23,8 @@ use make_it_braille as lib;
 mod cli;
 use cli::{
     util::{load_as_frames, try_get_from_url, Error},
-    Args, DitheringOption,
+    Args, ColorOption, Command, DitheringOption, FlipOption, FontOption, FormatOption,
+    RotateOption,
 };
 
 use crate::cli::Mode;
@@ -28,6 +32,15 @@ use crate::cli::Mode;
 fn main() -> Result<(), Error> {
     let args = Args::parse();
 
+    if args.two_color && args.color == ColorOption::None {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "--two-color requires --color to be set",
+            )
+            .exit();
+    }
+
     let dbg = match args.verbose {
         0 => "ERROR",
         1 => "INFO",
@@ -38,7 +51,88 @@ fn main() -> Result<(), Error> {
 
     debug!("parsed arguments: {args:#?}");
 
-    let mut image = match args.input {
+    let mut braille = match (&args.command, &args.input) {
+        (Some(Command::Text { text, font }), _) => text_banner(&text.join(" "), font)?,
+        (None, Some(input)) => match image_to_braille(&args, input)? {
+            Some(braille) => braille,
+            None => return Ok(()),
+        },
+        (None, None) => unreachable!("an input is required without a subcommand"),
+    };
+
+    if let Some(crop) = args.crop {
+        braille = braille.crop(crop.x, crop.y, crop.width, crop.height)?;
+    }
+    braille = match args.flip {
+        Some(FlipOption::Horizontal) => braille.flip_horizontal(),
+        Some(FlipOption::Vertical) => braille.flip_vertical(),
+        None => braille,
+    };
+    braille = match args.rotate {
+        Some(RotateOption::Rotate90) => braille.rotate90(),
+        Some(RotateOption::Rotate180) => braille.rotate180(),
+        Some(RotateOption::Rotate270) => braille.rotate270(),
+        None => braille,
+    };
+
+    let color_mode = match args.color {
+        ColorOption::None => None,
+        ColorOption::Truecolor => Some(ColorMode::TrueColor),
+        ColorOption::Ansi256 => Some(ColorMode::Ansi256),
+        ColorOption::Ansi16 => Some(ColorMode::Ansi16),
+    };
+    // draw raised dots the way they'd look in a terminal, so light dots on
+    // black unless dark pixels are raised
+    let (fg, bg) = if args.invert {
+        (Rgb::new(0, 0, 0), Rgb::new(255, 255, 255))
+    } else {
+        (Rgb::new(255, 255, 255), Rgb::new(0, 0, 0))
+    };
+    match (args.format, color_mode) {
+        (FormatOption::Html, _) => println!("{}", braille.as_html(!args.allow_blank_chars)),
+        (FormatOption::Svg, _) => {
+            let options = SvgOptions::default().foreground(fg).background(Some(bg));
+            print!("{}", braille.as_svg(&options))
+        }
+        (FormatOption::Png, _) => {
+            let options = RenderOptions::default().foreground(fg).background(Some(bg));
+            let mut png = Cursor::new(Vec::new());
+            braille
+                .render(&options)
+                .write_to(&mut png, image::ImageFormat::Png)?;
+            stdout().write_all(png.get_ref())?;
+        }
+        (FormatOption::Text, Some(mode)) => {
+            println!("{}", braille.as_ansi(!args.allow_blank_chars, true, mode))
+        }
+        (FormatOption::Text, None) => println!("{}", braille.as_str(!args.allow_blank_chars, true)),
+    }
+
+    Ok(())
+}
+
+/// renders `text` with the chosen font, with the text as raised dots
+fn text_banner(text: &str, font: &FontOption) -> Result<BrailleImg, Error> {
+    let loaded;
+    let font = match font {
+        FontOption::Ascii3x5 => Font::ascii_3x5(),
+        FontOption::Ascii5x7 => Font::ascii_5x7(),
+        FontOption::File(path) => {
+            debug!("loading font from {}", path.to_string_lossy());
+            loaded = Font::open(path)?;
+            &loaded
+        }
+    };
+    let (width, height) = font.measure(text);
+    let mut braille = BrailleImg::try_new(width, height)?;
+    braille.draw_text(0, 0, text, font);
+    Ok(braille)
+}
+
+/// loads the image from `input` and converts it, or returns [None] if there's
+/// nothing to convert
+fn image_to_braille(args: &Args, input: &Mode) -> Result<Option<BrailleImg>, Error> {
+    let mut image = match input {
         Mode::File(path) => {
             debug!("opening image as file");
             let buf = read(path)?;
@@ -56,7 +150,7 @@ fn main() -> Result<(), Error> {
         }
         Mode::Url(url) => {
             debug!("trying to fetch image as URL");
-            match try_get_from_url(url) {
+            match try_get_from_url(url.clone()) {
                 Ok(mut o) => match o.nth(args.frame.unwrap_or_default() as usize) {
                     Some(Ok(f)) => image::DynamicImage::ImageRgba8(f.into_buffer()),
                     Some(Err(e)) => {
@@ -95,8 +189,8 @@ fn main() -> Result<(), Error> {
             let cmd = std::env::args()
                 .next()
                 .unwrap_or_else(|| env!("CARGO_BIN_NAME").to_string());
-            generate(sh, &mut Args::command(), &cmd, &mut std::io::stdout());
-            return Ok(());
+            generate(*sh, &mut Args::command(), &cmd, &mut std::io::stdout());
+            return Ok(None);
         }
     };
 
@@ -128,6 +222,9 @@ fn main() -> Result<(), Error> {
     };
 
     debug!("target dimensions: {}x{}", width, height);
+    // report sizes that can't be turned into braille before resizing to them,
+    // since the converter panics on them
+    BrailleImg::check_dimensions(width, height)?;
 
     if (width, height) != image.dimensions() {
         image = image.resize_exact(width, height, image::imageops::FilterType::Triangle);
@@ -154,21 +251,78 @@ fn main() -> Result<(), Error> {
         image.save(out_dir.clone()).unwrap();
     }
 
-    let ditherer: Box<dyn Ditherer> = match args.dithering {
-        DitheringOption::Sierra2 => Box::new(dithering::Sierra2Row),
-        DitheringOption::None => Box::new(dithering::None),
-        DitheringOption::Bayer4x4 => Box::new(dithering::Bayer4x4),
-        DitheringOption::Bayer2x2 => Box::new(dithering::Bayer2x2),
+    let adaptive = |method: AdaptiveMethod| -> Box<dyn Ditherer> {
+        let ditherer = AdaptiveThreshold::new(method, args.window);
+        match args.bias {
+            Some(bias) => Box::new(ditherer.bias(bias)),
+            None => Box::new(ditherer),
+        }
     };
 
-    let braille = braille::BrailleImg::from_image(image, ditherer, !args.invert);
+    let ditherer: Box<dyn Ditherer> = if let Some(path) = &args.mask {
+        debug!("loading threshold mask from {}", path.to_string_lossy());
+        let mask = image::open(path)?.into_luma8();
+        Box::new(dithering::ThresholdMask::from_image(&mask))
+    } else {
+        match args.dithering {
+            DitheringOption::Sierra2 => Box::new(dithering::Sierra2Row.serpentine(args.serpentine)),
+            DitheringOption::Sierra3 => Box::new(dithering::Sierra3.serpentine(args.serpentine)),
+            DitheringOption::TwoRowSierra => {
+                Box::new(dithering::Sierra2.serpentine(args.serpentine))
+            }
+            DitheringOption::SierraLite => {
+                Box::new(dithering::SierraLite.serpentine(args.serpentine))
+            }
+            DitheringOption::FloydSteinberg => {
+                Box::new(dithering::FloydSteinberg.serpentine(args.serpentine))
+            }
+            DitheringOption::Atkinson => Box::new(dithering::Atkinson.serpentine(args.serpentine)),
+            DitheringOption::JarvisJudiceNinke => {
+                Box::new(dithering::JarvisJudiceNinke.serpentine(args.serpentine))
+            }
+            DitheringOption::Stucki => Box::new(dithering::Stucki.serpentine(args.serpentine)),
+            DitheringOption::Burkes => Box::new(dithering::Burkes.serpentine(args.serpentine)),
+            DitheringOption::AdaptiveMean => adaptive(AdaptiveMethod::Mean),
+            DitheringOption::AdaptiveGaussian => adaptive(AdaptiveMethod::Gaussian),
+            DitheringOption::Niblack => adaptive(AdaptiveMethod::Niblack),
+            DitheringOption::Sauvola => adaptive(AdaptiveMethod::Sauvola),
+            DitheringOption::None => Box::new(dithering::None),
+            DitheringOption::BlueNoise => Box::new(dithering::BlueNoise),
+            DitheringOption::Bayer16x16 => Box::new(dithering::Bayer::new(4)),
+            DitheringOption::Bayer8x8 => Box::new(dithering::Bayer::new(3)),
+            DitheringOption::Bayer4x4 => Box::new(dithering::Bayer4x4),
+            DitheringOption::Bayer2x2 => Box::new(dithering::Bayer2x2),
+        }
+    };
 
-    println!("{}", braille.as_str(!args.allow_blank_chars, true));
+    // the CLI raises light pixels by default, since terminals are usually dark
+    let raise = if args.invert {
+        Raise::Dark
+    } else {
+        Raise::Light
+    };
+    let converter = braille::BrailleImg::converter()
+        .ditherer(ditherer)
+        .threshold(args.threshold)
+        .raise(raise)
+        .linear(args.linear)
+        .background(args.background)
+        .gray_mode(args.gray_mode)
+        .colors(match (args.color, args.color_bg) {
+            (ColorOption::None, _) => CellColors::None,
+            (_, false) => CellColors::Foreground,
+            (_, true) => CellColors::ForegroundBackground,
+        });
+    let braille = if args.two_color {
+        converter.convert_two_color(image)
+    } else {
+        converter.convert(image)
+    };
 
     debug!(
         "turned image into braille in {}s",
         start.elapsed().as_secs_f32()
     );
 
-    Ok(())
+    Ok(Some(braille))
 }
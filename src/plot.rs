@@ -0,0 +1,539 @@
+//! charts drawn with braille characters, for plotting data in a terminal
+//!
+//! ```rust
+//! use make_it_braille::plot::{Chart, Scale, Series};
+//!
+//! let squares: Vec<_> = (1..=20).map(|x| (x as f64, (x * x) as f64)).collect();
+//!
+//! let chart = Chart::new(30, 8)
+//!     .y_scale(Scale::Log)
+//!     .series(Series::line(squares));
+//!
+//! println!("{}", chart.render());
+//! ```
+
+use crate::{
+    color::{CellColor, ColorMode, Rgb},
+    BrailleImg,
+};
+
+/// how values are mapped onto an axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scale {
+    #[default]
+    Linear,
+    /// logarithmic with base 10, where values that aren't positive are
+    /// left out
+    Log,
+}
+
+impl Scale {
+    /// the value in the space where the axis is linear, if it can be shown
+    fn apply(self, value: f64) -> Option<f64> {
+        match self {
+            Scale::Linear => value.is_finite().then_some(value),
+            Scale::Log => (value > 0.0 && value.is_finite()).then(|| value.log10()),
+        }
+    }
+
+    fn invert(self, value: f64) -> f64 {
+        match self {
+            Scale::Linear => value,
+            Scale::Log => 10f64.powf(value),
+        }
+    }
+}
+
+/// how the points of a [Series] are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesKind {
+    /// lines connecting each point to the next one
+    Line,
+    /// a single dot for each point
+    Scatter,
+    /// a bar from zero, or the bottom of the chart, to each point
+    Bar,
+}
+
+/// a set of (x, y) points drawn on a [Chart]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    kind: SeriesKind,
+    points: Vec<(f64, f64)>,
+    color: Option<Rgb>,
+}
+
+impl Series {
+    pub fn new(kind: SeriesKind, points: impl Into<Vec<(f64, f64)>>) -> Self {
+        Self {
+            kind,
+            points: points.into(),
+            color: None,
+        }
+    }
+
+    /// a series drawn as [SeriesKind::Line]
+    pub fn line(points: impl Into<Vec<(f64, f64)>>) -> Self {
+        Self::new(SeriesKind::Line, points)
+    }
+
+    /// a series drawn as [SeriesKind::Scatter]
+    pub fn scatter(points: impl Into<Vec<(f64, f64)>>) -> Self {
+        Self::new(SeriesKind::Scatter, points)
+    }
+
+    /// a series drawn as [SeriesKind::Bar]
+    pub fn bars(points: impl Into<Vec<(f64, f64)>>) -> Self {
+        Self::new(SeriesKind::Bar, points)
+    }
+
+    /// the color of characters this series is drawn in, where later series
+    /// win over earlier ones. defaults to none
+    pub fn color(mut self, color: Rgb) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn kind(&self) -> SeriesKind {
+        self.kind
+    }
+
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Axis {
+    scale: Scale,
+    range: Option<(f64, f64)>,
+    ticks: usize,
+}
+
+/// an axis with its range resolved, in the space where it is linear
+#[derive(Debug, Clone, Copy)]
+struct Mapping {
+    scale: Scale,
+    min: f64,
+    max: f64,
+    /// size of the axis in dots
+    size: u32,
+    flipped: bool,
+}
+
+impl Mapping {
+    /// the dot a value is drawn at, which can be outside of the chart
+    fn to_dot(self, value: f64) -> Option<i32> {
+        let t = (self.scale.apply(value)? - self.min) / (self.max - self.min);
+        let t = if self.flipped { 1.0 - t } else { t };
+        // far away points are moved closer, so lines to them can't take forever
+        Some((t * (self.size - 1) as f64).round().clamp(-1e6, 1e6) as i32)
+    }
+
+    /// values of `count` evenly spaced ticks from the start to the end of the
+    /// axis
+    fn ticks(self, count: usize) -> Vec<f64> {
+        let count = count.max(2);
+        (0..count)
+            .map(|i| {
+                let t = self.min + (self.max - self.min) * i as f64 / (count - 1) as f64;
+                self.scale.invert(t)
+            })
+            .collect()
+    }
+
+    /// a label for a tick, with as many decimals as the range of the axis
+    /// needs
+    fn label(self, value: f64) -> String {
+        let reference = match self.scale {
+            Scale::Linear => self.max - self.min,
+            Scale::Log => value,
+        };
+        if value == 0.0 {
+            return "0".to_string();
+        }
+        if !(1e-4..1e6).contains(&value.abs()) {
+            return format!("{value:.1e}");
+        }
+        let decimals = (1.0 - reference.abs().log10().floor()).clamp(0.0, 6.0) as usize;
+        let label = format!("{value:.decimals$}");
+        match label.strip_prefix('-') {
+            Some(positive) if positive.chars().all(|c| c == '0' || c == '.') => {
+                positive.to_string()
+            }
+            _ => label,
+        }
+    }
+}
+
+/// a chart of one or more [Series], with axes that are scaled to fit all of
+/// them unless their ranges are set
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chart {
+    width: u32,
+    height: u32,
+    x: Axis,
+    y: Axis,
+    series: Vec<Series>,
+}
+
+impl Chart {
+    /// creates a chart with a plot area that is `width` by `height`
+    /// characters, not counting the axes and labels around it
+    /// # Panics
+    /// if either width or height is 0
+    pub fn new(width: u32, height: u32) -> Self {
+        assert!(
+            width != 0 && height != 0,
+            "width and height must be greater than 0"
+        );
+        let axis = Axis {
+            scale: Scale::Linear,
+            range: None,
+            ticks: 3,
+        };
+        Self {
+            width,
+            height,
+            x: axis,
+            y: axis,
+            series: Vec::new(),
+        }
+    }
+
+    /// adds a series to the chart
+    pub fn series(mut self, series: Series) -> Self {
+        self.series.push(series);
+        self
+    }
+
+    /// the scale of the x axis, defaults to [Scale::Linear]
+    pub fn x_scale(mut self, scale: Scale) -> Self {
+        self.x.scale = scale;
+        self
+    }
+
+    /// the scale of the y axis, defaults to [Scale::Linear]
+    pub fn y_scale(mut self, scale: Scale) -> Self {
+        self.y.scale = scale;
+        self
+    }
+
+    /// the values at the left and right edges of the chart, which are picked
+    /// to fit every series by default
+    pub fn x_range(mut self, min: f64, max: f64) -> Self {
+        self.x.range = Some((min, max));
+        self
+    }
+
+    /// the values at the bottom and top edges of the chart, which are picked
+    /// to fit every series by default, including zero if there are bars
+    pub fn y_range(mut self, min: f64, max: f64) -> Self {
+        self.y.range = Some((min, max));
+        self
+    }
+
+    /// how many labeled ticks each axis has, including both ends, defaults
+    /// to 3 for both. the y axis has at most one tick per row of characters
+    pub fn ticks(mut self, x: usize, y: usize) -> Self {
+        self.x.ticks = x;
+        self.y.ticks = y;
+        self
+    }
+
+    /// resolves the range of the x axis, or the y axis if `vertical` is true
+    fn mapping(&self, vertical: bool) -> Mapping {
+        let (axis, size) = if vertical {
+            (self.y, self.height * 4)
+        } else {
+            (self.x, self.width * 2)
+        };
+        let (min, max) = match axis.range {
+            Some((min, max)) => (
+                axis.scale.apply(min).unwrap_or(0.0),
+                axis.scale.apply(max).unwrap_or(1.0),
+            ),
+            None => {
+                let mut range = (f64::INFINITY, f64::NEG_INFINITY);
+                for series in &self.series {
+                    let zero = (vertical && series.kind == SeriesKind::Bar).then_some(0.0);
+                    let values = series
+                        .points
+                        .iter()
+                        .map(|&(x, y)| if vertical { y } else { x })
+                        .chain(zero);
+                    for t in values.filter_map(|v| axis.scale.apply(v)) {
+                        range = (range.0.min(t), range.1.max(t));
+                    }
+                }
+                if range.0 > range.1 {
+                    (0.0, 1.0)
+                } else {
+                    range
+                }
+            }
+        };
+        let (min, max) = if min == max {
+            (min - 1.0, max + 1.0)
+        } else {
+            (min, max)
+        };
+        Mapping {
+            scale: axis.scale,
+            min,
+            max,
+            size,
+            flipped: vertical,
+        }
+    }
+
+    fn mappings(&self) -> (Mapping, Mapping) {
+        (self.mapping(false), self.mapping(true))
+    }
+
+    /// draws every series onto a [BrailleImg] of only the plot area, where
+    /// characters get the color of the last series with dots in them
+    pub fn draw(&self) -> BrailleImg {
+        let (x, y) = self.mappings();
+        let mut img = BrailleImg::new(self.width * 2, self.height * 4);
+
+        for series in &self.series {
+            let mut layer = BrailleImg::new(img.width(), img.height());
+            let points: Vec<_> = series
+                .points
+                .iter()
+                .map(|&(px, py)| x.to_dot(px).zip(y.to_dot(py)))
+                .collect();
+
+            match series.kind {
+                SeriesKind::Line => {
+                    // invalid points split the line
+                    for pair in points.windows(2) {
+                        match pair {
+                            [Some(a), Some(b)] => layer.draw_line(a.0, a.1, b.0, b.1, true),
+                            [Some(a), None] | [None, Some(a)] => {
+                                layer.draw_line(a.0, a.1, a.0, a.1, true)
+                            }
+                            _ => {}
+                        }
+                    }
+                    if let [Some(a)] = points[..] {
+                        layer.draw_line(a.0, a.1, a.0, a.1, true);
+                    }
+                }
+                SeriesKind::Scatter => {
+                    for (px, py) in points.iter().flatten() {
+                        layer.draw_line(*px, *py, *px, *py, true);
+                    }
+                }
+                SeriesKind::Bar => {
+                    let base = y
+                        .to_dot(0.0)
+                        .unwrap_or(y.size as i32 - 1)
+                        .clamp(0, y.size as i32 - 1);
+                    // bars are as wide as they can be without touching
+                    let mut columns: Vec<i32> = points.iter().flatten().map(|p| p.0).collect();
+                    columns.sort_unstable();
+                    columns.dedup();
+                    let bar_width = columns
+                        .windows(2)
+                        .map(|w| w[1].abs_diff(w[0]) - 1)
+                        .min()
+                        .unwrap_or(1)
+                        .max(1);
+                    for &(px, py) in points.iter().flatten() {
+                        let left = px - (bar_width as i32 - 1) / 2;
+                        let top = py.min(base);
+                        let height = py.abs_diff(base) + 1;
+                        layer.draw_rect(left, top, bar_width, height, true, true);
+                    }
+                }
+            }
+
+            for dy in 0..layer.height() {
+                for dx in 0..layer.width() {
+                    if layer.get_dot(dx, dy) != Some(true) {
+                        continue;
+                    }
+                    let _ = img.set_dot(dx, dy, true);
+                    if let Some(color) = series.color {
+                        let cell = CellColor {
+                            foreground: Some(color),
+                            background: None,
+                        };
+                        let _ = img.set_cell_color(dx / 2, dy / 4, cell);
+                    }
+                }
+            }
+        }
+        img
+    }
+
+    /// renders the chart as text, with the y axis and its labels on the left
+    /// and the x axis and its labels below. empty braille characters are kept,
+    /// so the plot area is blank
+    pub fn render(&self) -> String {
+        self.layout(self.draw().as_str(false, true))
+    }
+
+    /// renders the chart like [Chart::render], but with the colors of each
+    /// series as ANSI escape codes
+    pub fn render_ansi(&self, mode: ColorMode) -> String {
+        self.layout(self.draw().as_ansi(false, true, mode))
+    }
+
+    /// puts the axes and their labels around the rows of the plot area
+    fn layout(&self, plot: String) -> String {
+        let (x, y) = self.mappings();
+
+        let mut y_labels = vec![None; self.height as usize];
+        for value in y.ticks(self.y.ticks.min(self.height as usize)) {
+            if let Some(dot) = y.to_dot(value) {
+                let row = (dot.clamp(0, y.size as i32 - 1) / 4) as usize;
+                y_labels[row] = Some(y.label(value));
+            }
+        }
+        let margin = y_labels
+            .iter()
+            .flatten()
+            .map(|l| l.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut axis: Vec<char> = vec!['─'; self.width as usize];
+        let mut x_labels: Vec<char> = vec![' '; self.width as usize + 1];
+        let mut free_from = 0;
+        for value in x.ticks(self.x.ticks) {
+            let Some(dot) = x.to_dot(value) else {
+                continue;
+            };
+            let col = (dot.clamp(0, x.size as i32 - 1) / 2) as usize;
+            axis[col] = '┬';
+            // labels are centered on their tick, unless they'd overlap
+            let label: Vec<char> = x.label(value).chars().collect();
+            let start = (col + 1).saturating_sub(label.len() / 2).max(free_from);
+            if start + label.len() > x_labels.len() {
+                x_labels.resize(start + label.len(), ' ');
+            }
+            x_labels[start..start + label.len()].copy_from_slice(&label);
+            free_from = start + label.len() + 1;
+        }
+
+        let mut out = String::new();
+        for (row, label) in plot.split('\n').zip(&y_labels) {
+            match label {
+                Some(label) => out.push_str(&format!("{label:>margin$} ┤{row}\n")),
+                None => out.push_str(&format!("{:margin$} │{row}\n", "")),
+            }
+        }
+        out.push_str(&format!(
+            "{:margin$} └{}\n",
+            "",
+            axis.iter().collect::<String>()
+        ));
+        out.push_str(&format!(
+            "{:margin$} {}",
+            "",
+            x_labels.iter().collect::<String>().trim_end()
+        ));
+        out
+    }
+}
+
+/// draws `values` as a single row of `width` characters, where each column
+/// of dots is filled from the bottom up to its value, and values that aren't
+/// finite are left empty
+/// # Panics
+/// if width is 0
+pub fn sparkline(values: &[f64], width: u32) -> String {
+    let mut img = BrailleImg::new(width * 2, 4);
+    let finite = values.iter().copied().filter(|v| v.is_finite());
+    let min = finite.clone().fold(f64::INFINITY, f64::min);
+    let max = finite.fold(f64::NEG_INFINITY, f64::max);
+
+    let columns = img.width() as usize;
+    for x in 0..columns {
+        // average the values that fall into this column, or repeat them if
+        // there are less values than columns
+        let start = x * values.len() / columns;
+        let end = ((x + 1) * values.len() / columns)
+            .max(start + 1)
+            .min(values.len());
+        let Some(bucket) = values.get(start..end).filter(|b| !b.is_empty()) else {
+            continue;
+        };
+        let value = bucket.iter().sum::<f64>() / bucket.len() as f64;
+        if !value.is_finite() {
+            continue;
+        }
+        let t = if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.5
+        };
+        let level = 1 + (t * 3.0).round() as u32;
+        for y in 4 - level..4 {
+            let _ = img.set_dot(x as u32, y, true);
+        }
+    }
+    img.as_str(false, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chart_layout() {
+        let chart = Chart::new(5, 2)
+            .series(Series::line([(0.0, 0.0), (10.0, 10.0)]))
+            .ticks(3, 2);
+        assert_eq!(
+            chart.render(),
+            "10 ┤⠀⠀⢀⠤⠊\n 0 ┤⡠⠒⠁⠀⠀\n   └┬─┬─┬\n    0 5 10"
+        );
+    }
+
+    #[test]
+    fn chart_scales() {
+        let chart = Chart::new(4, 2)
+            .y_scale(Scale::Log)
+            .series(Series::scatter([
+                (0.0, 1.0),
+                (1.0, 10.0),
+                (2.0, 100.0),
+                (3.0, -5.0),
+            ]));
+        let (_, y) = chart.mappings();
+        assert_eq!((y.min, y.max), (0.0, 2.0));
+        assert_eq!(y.to_dot(10.0), Some(4));
+        assert_eq!(y.to_dot(-5.0), None);
+        assert_eq!(y.label(100.0), "100");
+        assert_eq!(y.label(0.01), "0.010");
+
+        // bars always start at zero
+        let chart = Chart::new(2, 1).series(Series::bars([(0.0, 2.0), (1.0, 4.0)]));
+        let (_, y) = chart.mappings();
+        assert_eq!((y.min, y.max), (0.0, 4.0));
+        assert_eq!(chart.draw().as_str(false, true), "⣤⢸");
+
+        let chart = Chart::new(2, 1)
+            .x_range(0.0, 1.0)
+            .y_range(-1.0, 1.0)
+            .series(Series::line([(0.0, -100.0), (1.0, 100.0)]).color(Rgb::new(255, 0, 0)));
+        let img = chart.draw();
+        assert_eq!(
+            img.cell_color(0, 0).unwrap().foreground,
+            Some(Rgb::new(255, 0, 0))
+        );
+        assert!(chart.render_ansi(ColorMode::Ansi16).contains("\x1b[91m"));
+    }
+
+    #[test]
+    fn sparklines() {
+        assert_eq!(sparkline(&[0.0, 1.0, 2.0, 3.0], 2), "⣠⣾");
+        assert_eq!(sparkline(&[1.0, 1.0], 1), "⣶");
+        assert_eq!(sparkline(&[0.0, f64::NAN, 3.0, 3.0], 2), "⡀⣿");
+        assert_eq!(sparkline(&[], 1), "⠀");
+    }
+}
@@ -0,0 +1,336 @@
+//! bitmap fonts for drawing text onto a [BrailleImg] at dot resolution
+//!
+//! ```rust
+//! use make_it_braille::{BrailleImg, text::Font};
+//!
+//! let font = Font::ascii_5x7();
+//! let (width, height) = font.measure("hello!");
+//!
+//! let mut img = BrailleImg::new(width, height);
+//! img.draw_text(0, 0, "hello!", font);
+//!
+//! println!("{}", img.as_str(false, true));
+//! ```
+
+use std::{collections::HashMap, path::Path};
+
+use crate::BrailleImg;
+
+mod bdf;
+mod builtin;
+mod psf;
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(std::io::Error),
+    /// the BDF font was invalid at the given line, starting at 1
+    InvalidBdf(usize),
+    /// the PSF font was invalid or cut short
+    InvalidPsf,
+    /// the file was neither a BDF nor a PSF font
+    UnknownFormat,
+}
+
+impl std::error::Error for FontError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FontError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FontError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FontError::Io(e) => write!(f, "failed to read the font: {e}"),
+            FontError::InvalidBdf(line) => write!(f, "invalid BDF font at line {line}"),
+            FontError::InvalidPsf => write!(f, "invalid PSF font"),
+            FontError::UnknownFormat => write!(f, "the font is neither in BDF nor PSF format"),
+        }
+    }
+}
+
+impl From<std::io::Error> for FontError {
+    fn from(e: std::io::Error) -> Self {
+        FontError::Io(e)
+    }
+}
+
+/// a single character of a [Font]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Glyph {
+    width: u32,
+    height: u32,
+    /// offset of the bitmap from the pen position
+    x_offset: i32,
+    /// offset of the bitmap from the top of the line
+    y_offset: i32,
+    /// how far the pen moves after this glyph
+    advance: u32,
+    /// whether each dot is set, row by row
+    bits: Vec<bool>,
+}
+
+impl Glyph {
+    fn from_fn(width: u32, height: u32, f: impl Fn(u32, u32) -> bool) -> Self {
+        let bits = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Self {
+            width,
+            height,
+            x_offset: 0,
+            y_offset: 0,
+            advance: width,
+            bits,
+        }
+    }
+}
+
+/// a bitmap font, either one of the embedded ones or loaded from a BDF or
+/// PSF file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    glyphs: HashMap<char, Glyph>,
+    line_height: u32,
+}
+
+impl Font {
+    /// an embedded font covering printable ASCII, with glyphs that are 3 dots
+    /// wide and 5 dots tall
+    pub fn ascii_3x5() -> &'static Font {
+        builtin::ascii_3x5()
+    }
+
+    /// an embedded font covering printable ASCII, with glyphs that are 5 dots
+    /// wide and 7 dots tall, so each line of text is 2 characters tall
+    pub fn ascii_5x7() -> &'static Font {
+        builtin::ascii_5x7()
+    }
+
+    /// parses a font in the Glyph Bitmap Distribution Format, skipping glyphs
+    /// that don't map to a unicode character
+    /// # Errors
+    /// [FontError::InvalidBdf] with the line of the first invalid value
+    pub fn from_bdf(source: &str) -> Result<Self, FontError> {
+        bdf::parse(source)
+    }
+
+    /// parses a font in version 1 or 2 of the PC Screen Font format, as used
+    /// by the Linux console, using its unicode table if it has one
+    /// # Errors
+    /// [FontError::InvalidPsf] if the font is invalid or cut short
+    pub fn from_psf(bytes: &[u8]) -> Result<Self, FontError> {
+        psf::parse(bytes)
+    }
+
+    /// loads a BDF or PSF font from a file, detecting which one it is from
+    /// its contents
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        let bytes = std::fs::read(path)?;
+        if psf::is_psf(&bytes) {
+            return Self::from_psf(&bytes);
+        }
+        match std::str::from_utf8(&bytes) {
+            Ok(source) if source.trim_start().starts_with("STARTFONT") => Self::from_bdf(source),
+            _ => Err(FontError::UnknownFormat),
+        }
+    }
+
+    /// distance in dots between the tops of two lines of text
+    pub fn line_height(&self) -> u32 {
+        self.line_height
+    }
+
+    /// if the font has a glyph for `c`, otherwise `?` is drawn instead, or
+    /// nothing if there's no glyph for it either
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+
+    fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c).or_else(|| self.glyphs.get(&'?'))
+    }
+
+    /// calls `f` with the position of every dot of `text`, where lines are
+    /// separated by `\n`
+    fn layout(&self, text: &str, mut f: impl FnMut(i64, i64)) {
+        for (line, text) in text.lines().enumerate() {
+            let top = (line as u32 * self.line_height) as i64;
+            let mut pen = 0i64;
+            for glyph in text.chars().filter_map(|c| self.glyph(c)) {
+                for (i, _) in glyph.bits.iter().enumerate().filter(|(_, bit)| **bit) {
+                    let x = (i as u32 % glyph.width) as i64 + glyph.x_offset as i64;
+                    let y = (i as u32 / glyph.width) as i64 + glyph.y_offset as i64;
+                    f(pen + x, top + y);
+                }
+                pen += glyph.advance as i64;
+            }
+        }
+    }
+
+    /// the size in dots of the area `text` covers when it is drawn with this
+    /// font, where the height is always a whole number of lines
+    pub fn measure(&self, text: &str) -> (u32, u32) {
+        let mut width = 0;
+        for line in text.lines() {
+            let mut pen = 0i64;
+            for glyph in line.chars().filter_map(|c| self.glyph(c)) {
+                let right = pen + glyph.x_offset as i64 + glyph.width as i64;
+                width = width.max(right);
+                pen += glyph.advance as i64;
+            }
+        }
+        let lines = text.lines().count() as u32;
+        (width.max(0) as u32, lines * self.line_height)
+    }
+}
+
+impl BrailleImg {
+    /// raises the dots of `text` drawn with `font`, with the top left of its
+    /// first line at (`x`, `y`), where lines are separated by `\n`. parts of
+    /// the text outside of the image are clipped silently
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, font: &Font) {
+        font.layout(text, |dx, dy| {
+            let (px, py) = (x as i64 + dx, y as i64 + dy);
+            if let (Ok(px), Ok(py)) = (u32::try_from(px), u32::try_from(py)) {
+                let _ = self.set_dot(px, py, true);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_fonts() {
+        for font in [Font::ascii_3x5(), Font::ascii_5x7()] {
+            assert!((' '..='~').all(|c| font.has_glyph(c)));
+            assert!(!font.has_glyph('é'));
+        }
+        assert_eq!(Font::ascii_3x5().measure("ab\nc"), (7, 12));
+        assert_eq!(Font::ascii_5x7().measure("ab\nc"), (11, 16));
+        assert_eq!(Font::ascii_5x7().measure(""), (0, 0));
+
+        let mut img = BrailleImg::new(8, 8);
+        img.draw_text(0, 0, "H", Font::ascii_5x7());
+        assert_eq!(img.as_str(false, true), "⣇⣀⡇⠀\n⠇⠀⠇⠀");
+
+        let mut img = BrailleImg::new(4, 8);
+        img.draw_text(-2, 1, "1é", Font::ascii_3x5());
+        assert_eq!(img.as_str(false, true), "⠀⢒\n⠂⠐");
+    }
+
+    #[test]
+    fn bdf_font() {
+        let source = "STARTFONT 2.1
+FONTBOUNDINGBOX 4 6 0 -2
+STARTPROPERTIES 2
+FONT_ASCENT 4
+FONT_DESCENT 2
+ENDPROPERTIES
+CHARS 2
+STARTCHAR A
+ENCODING 65
+DWIDTH 5 0
+BBX 3 3 1 0
+BITMAP
+40
+A0
+E0
+ENDCHAR
+STARTCHAR unmapped
+ENCODING -1
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+        let font = Font::from_bdf(source).unwrap();
+        assert_eq!(font.line_height(), 6);
+        assert_eq!(font.measure("AA"), (9, 6));
+        let glyph = &font.glyphs[&'A'];
+        assert_eq!((glyph.x_offset, glyph.y_offset, glyph.advance), (1, 1, 5));
+        assert_eq!(
+            glyph.bits,
+            [false, true, false, true, false, true, true, true, true]
+        );
+
+        let broken = source.replace("A0", "G0");
+        assert!(matches!(
+            Font::from_bdf(&broken),
+            Err(FontError::InvalidBdf(14))
+        ));
+        assert!(matches!(
+            Font::from_bdf(&source[..source.find("ENDCHAR").unwrap()]),
+            Err(FontError::InvalidBdf(_))
+        ));
+        for (bbx, line) in [
+            ("BBX 65536 65536 0 0", 11),
+            ("BBX 4 4 0 4294967296", 11),
+            ("BBX 32768 32768 0 0", 12),
+        ] {
+            assert!(matches!(
+                Font::from_bdf(&source.replace("BBX 3 3 1 0", bbx)),
+                Err(FontError::InvalidBdf(l)) if l == line
+            ));
+        }
+
+        // header values that would overflow when placing glyphs
+        for (from, to, line) in [
+            ("FONT_ASCENT 4", "FONT_ASCENT 2147483647", 4),
+            ("FONT_ASCENT 4", "FONT_ASCENT 4294967296", 4),
+            ("FONT_DESCENT 2", "FONT_DESCENT 9223372036854775807", 5),
+            (
+                "FONTBOUNDINGBOX 4 6 0 -2",
+                "FONTBOUNDINGBOX 4 6 0 -65536",
+                2,
+            ),
+        ] {
+            assert!(matches!(
+                Font::from_bdf(&source.replace(from, to)),
+                Err(FontError::InvalidBdf(l)) if l == line
+            ));
+        }
+    }
+
+    #[test]
+    fn psf_fonts() {
+        // version 2, 2 glyphs of 10x2 dots with a unicode table
+        let mut psf2 = vec![0x72, 0xb5, 0x4a, 0x86];
+        for field in [0u32, 32, 1, 2, 4, 2, 10] {
+            psf2.extend(field.to_le_bytes());
+        }
+        psf2.extend([0xff, 0xc0, 0x00, 0x40, 0x00, 0x00, 0x80, 0x00]);
+        psf2.extend(b"ab\xfexy\xff");
+        psf2.extend("é".as_bytes());
+        psf2.push(0xff);
+
+        let font = Font::from_psf(&psf2).unwrap();
+        assert_eq!(font.line_height(), 2);
+        assert!(font.has_glyph('a') && font.has_glyph('b') && font.has_glyph('é'));
+        assert!(!font.has_glyph('x'));
+        assert_eq!(font.glyphs[&'a'].bits.iter().filter(|b| **b).count(), 11);
+        assert!(font.glyphs[&'é'].bits[10]);
+        assert!(matches!(
+            Font::from_psf(&psf2[..40]),
+            Err(FontError::InvalidPsf)
+        ));
+
+        // version 1, without a unicode table
+        let mut psf1 = vec![0x36, 0x04, 0x00, 0x01];
+        psf1.extend((0..=255).map(|i| if i == b'x' { 0x81 } else { 0 }));
+        let font = Font::from_psf(&psf1).unwrap();
+        assert_eq!(font.measure("xx"), (16, 1));
+        assert!(font.glyphs[&'x'].bits[7]);
+        assert!(matches!(
+            Font::from_psf(&psf1[..100]),
+            Err(FontError::InvalidPsf)
+        ));
+    }
+}
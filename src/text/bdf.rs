@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use super::{Font, FontError, Glyph};
+
+/// the most dots a glyph can have, which is far more than any real font
+/// needs, so a broken bounding box can't allocate all memory
+const MAX_GLYPH_DOTS: u32 = 1 << 20;
+
+/// the largest distance in dots, either positive or negative, that a font or
+/// glyph can give for its ascent, descent or bounding box, so placing glyphs
+/// can't overflow
+const MAX_DISTANCE: i64 = 1 << 15;
+
+/// parses `words` as exactly `N` numbers
+fn numbers<'a, const N: usize>(
+    mut words: impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<[i64; N], FontError> {
+    let mut values = [0; N];
+    for value in &mut values {
+        *value = words
+            .next()
+            .and_then(|w| w.parse().ok())
+            .ok_or(FontError::InvalidBdf(line))?;
+    }
+    Ok(values)
+}
+
+/// parses `words` like [numbers], where every number is at most
+/// [MAX_DISTANCE] away from 0
+fn distances<'a, const N: usize>(
+    words: impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<[i64; N], FontError> {
+    let values = numbers::<N>(words, line)?;
+    if values.iter().any(|v| v.abs() > MAX_DISTANCE) {
+        return Err(FontError::InvalidBdf(line));
+    }
+    Ok(values)
+}
+
+pub(super) fn parse(source: &str) -> Result<Font, FontError> {
+    let mut glyphs = HashMap::new();
+    let mut bounding_box = None;
+    let mut ascent = None;
+    let mut descent = None;
+
+    let mut lines = source.lines().enumerate().map(|(i, l)| (i + 1, l.trim()));
+    while let Some((n, line)) = lines.next() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("FONTBOUNDINGBOX") => bounding_box = Some(distances::<4>(words, n)?),
+            Some("FONT_ASCENT") => ascent = Some(distances::<1>(words, n)?[0]),
+            Some("FONT_DESCENT") => descent = Some(distances::<1>(words, n)?[0]),
+            Some("STARTCHAR") => {
+                if let (Some(c), glyph) = parse_glyph(&mut lines, n)? {
+                    glyphs.insert(c, glyph);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // glyphs are placed relative to the baseline until the ascent is known
+    let ascent = ascent
+        .or(bounding_box.map(|[_, h, _, y]| h + y))
+        .unwrap_or_else(|| {
+            glyphs
+                .values()
+                .map(|g| -g.y_offset as i64)
+                .max()
+                .unwrap_or(0)
+        });
+    let descent = descent
+        .or(bounding_box.map(|[_, _, _, y]| -y))
+        .unwrap_or_else(|| {
+            let bottom = glyphs.values().map(|g| g.y_offset as i64 + g.height as i64);
+            bottom.max().unwrap_or(0)
+        });
+    for glyph in glyphs.values_mut() {
+        glyph.y_offset = glyph.y_offset.saturating_add(ascent as i32);
+    }
+
+    Ok(Font {
+        glyphs,
+        line_height: (ascent + descent).max(1) as u32,
+    })
+}
+
+/// parses the lines of a glyph after `STARTCHAR` until `ENDCHAR`, returning
+/// the character it is for if it has one
+fn parse_glyph<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    start: usize,
+) -> Result<(Option<char>, Glyph), FontError> {
+    let mut encoding = None;
+    let mut advance = None;
+    let mut bbx = None;
+    let mut glyph = None;
+    let mut last = start;
+
+    while let Some((n, line)) = lines.next() {
+        last = n;
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("ENCODING") => encoding = Some(numbers::<1>(words, n)?[0]),
+            Some("DWIDTH") => advance = Some(numbers::<1>(words, n)?[0]),
+            Some("BBX") => bbx = Some(distances::<4>(words, n)?),
+            Some("BITMAP") => {
+                let [w, h, x, y] = bbx.ok_or(FontError::InvalidBdf(n))?;
+                let invalid = |_| FontError::InvalidBdf(n);
+                let (w, h) = (
+                    u32::try_from(w).map_err(invalid)?,
+                    u32::try_from(h).map_err(invalid)?,
+                );
+                let size = w
+                    .checked_mul(h)
+                    .filter(|&size| size <= MAX_GLYPH_DOTS)
+                    .ok_or(FontError::InvalidBdf(n))?;
+                let mut bits = Vec::with_capacity(size as usize);
+                for _ in 0..h {
+                    let (n, row) = lines.next().ok_or(FontError::InvalidBdf(n))?;
+                    let row = row.as_bytes();
+                    for x in 0..w as usize {
+                        let digit = row
+                            .get(x / 4)
+                            .and_then(|d| (*d as char).to_digit(16))
+                            .ok_or(FontError::InvalidBdf(n))?;
+                        bits.push(digit & (0b1000 >> (x % 4)) != 0);
+                    }
+                }
+                glyph = Some(Glyph {
+                    width: w,
+                    height: h,
+                    x_offset: x as i32,
+                    y_offset: -(h as i32 + y as i32),
+                    advance: advance.unwrap_or(w as i64).max(0) as u32,
+                    bits,
+                });
+            }
+            Some("ENDCHAR") => {
+                let glyph = glyph.ok_or(FontError::InvalidBdf(n))?;
+                let c = encoding
+                    .and_then(|e| u32::try_from(e).ok())
+                    .and_then(char::from_u32);
+                return Ok((c, glyph));
+            }
+            _ => {}
+        }
+    }
+    Err(FontError::InvalidBdf(last))
+}
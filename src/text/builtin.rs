@@ -0,0 +1,232 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use super::{Font, Glyph};
+
+/// glyphs of the 3x5 font from ' ' to '~', one row per value with the
+/// leftmost dot in the highest bit
+#[rustfmt::skip]
+const ASCII_3X5: [[u8; 5]; 95] = [
+    [0b000, 0b000, 0b000, 0b000, 0b000], // space
+    [0b010, 0b010, 0b010, 0b000, 0b010], // !
+    [0b101, 0b101, 0b000, 0b000, 0b000], // "
+    [0b101, 0b111, 0b101, 0b111, 0b101], // #
+    [0b011, 0b110, 0b010, 0b011, 0b110], // $
+    [0b101, 0b001, 0b010, 0b100, 0b101], // %
+    [0b010, 0b101, 0b010, 0b101, 0b011], // &
+    [0b010, 0b010, 0b000, 0b000, 0b000], // '
+    [0b001, 0b010, 0b010, 0b010, 0b001], // (
+    [0b100, 0b010, 0b010, 0b010, 0b100], // )
+    [0b000, 0b101, 0b010, 0b101, 0b000], // *
+    [0b000, 0b010, 0b111, 0b010, 0b000], // +
+    [0b000, 0b000, 0b000, 0b010, 0b100], // ,
+    [0b000, 0b000, 0b111, 0b000, 0b000], // -
+    [0b000, 0b000, 0b000, 0b000, 0b010], // .
+    [0b001, 0b001, 0b010, 0b100, 0b100], // /
+    [0b111, 0b101, 0b101, 0b101, 0b111], // 0
+    [0b010, 0b110, 0b010, 0b010, 0b111], // 1
+    [0b111, 0b001, 0b111, 0b100, 0b111], // 2
+    [0b111, 0b001, 0b111, 0b001, 0b111], // 3
+    [0b101, 0b101, 0b111, 0b001, 0b001], // 4
+    [0b111, 0b100, 0b111, 0b001, 0b111], // 5
+    [0b111, 0b100, 0b111, 0b101, 0b111], // 6
+    [0b111, 0b001, 0b001, 0b001, 0b001], // 7
+    [0b111, 0b101, 0b111, 0b101, 0b111], // 8
+    [0b111, 0b101, 0b111, 0b001, 0b111], // 9
+    [0b000, 0b010, 0b000, 0b010, 0b000], // :
+    [0b000, 0b010, 0b000, 0b010, 0b100], // ;
+    [0b001, 0b010, 0b100, 0b010, 0b001], // <
+    [0b000, 0b111, 0b000, 0b111, 0b000], // =
+    [0b100, 0b010, 0b001, 0b010, 0b100], // >
+    [0b111, 0b001, 0b011, 0b000, 0b010], // ?
+    [0b010, 0b101, 0b111, 0b100, 0b011], // @
+    [0b010, 0b101, 0b111, 0b101, 0b101], // A
+    [0b110, 0b101, 0b110, 0b101, 0b110], // B
+    [0b011, 0b100, 0b100, 0b100, 0b011], // C
+    [0b110, 0b101, 0b101, 0b101, 0b110], // D
+    [0b111, 0b100, 0b110, 0b100, 0b111], // E
+    [0b111, 0b100, 0b110, 0b100, 0b100], // F
+    [0b011, 0b100, 0b101, 0b101, 0b011], // G
+    [0b101, 0b101, 0b111, 0b101, 0b101], // H
+    [0b111, 0b010, 0b010, 0b010, 0b111], // I
+    [0b001, 0b001, 0b001, 0b101, 0b010], // J
+    [0b101, 0b101, 0b110, 0b101, 0b101], // K
+    [0b100, 0b100, 0b100, 0b100, 0b111], // L
+    [0b101, 0b111, 0b111, 0b101, 0b101], // M
+    [0b110, 0b101, 0b101, 0b101, 0b101], // N
+    [0b010, 0b101, 0b101, 0b101, 0b010], // O
+    [0b110, 0b101, 0b110, 0b100, 0b100], // P
+    [0b010, 0b101, 0b101, 0b110, 0b011], // Q
+    [0b110, 0b101, 0b110, 0b101, 0b101], // R
+    [0b011, 0b100, 0b010, 0b001, 0b110], // S
+    [0b111, 0b010, 0b010, 0b010, 0b010], // T
+    [0b101, 0b101, 0b101, 0b101, 0b111], // U
+    [0b101, 0b101, 0b101, 0b101, 0b010], // V
+    [0b101, 0b101, 0b111, 0b111, 0b101], // W
+    [0b101, 0b101, 0b010, 0b101, 0b101], // X
+    [0b101, 0b101, 0b010, 0b010, 0b010], // Y
+    [0b111, 0b001, 0b010, 0b100, 0b111], // Z
+    [0b110, 0b100, 0b100, 0b100, 0b110], // [
+    [0b100, 0b100, 0b010, 0b001, 0b001], // backslash
+    [0b011, 0b001, 0b001, 0b001, 0b011], // ]
+    [0b010, 0b101, 0b000, 0b000, 0b000], // ^
+    [0b000, 0b000, 0b000, 0b000, 0b111], // _
+    [0b100, 0b010, 0b000, 0b000, 0b000], // `
+    [0b000, 0b011, 0b101, 0b101, 0b011], // a
+    [0b100, 0b110, 0b101, 0b101, 0b110], // b
+    [0b000, 0b011, 0b100, 0b100, 0b011], // c
+    [0b001, 0b011, 0b101, 0b101, 0b011], // d
+    [0b000, 0b010, 0b111, 0b100, 0b011], // e
+    [0b001, 0b010, 0b111, 0b010, 0b010], // f
+    [0b000, 0b011, 0b101, 0b011, 0b110], // g
+    [0b100, 0b110, 0b101, 0b101, 0b101], // h
+    [0b010, 0b000, 0b010, 0b010, 0b010], // i
+    [0b001, 0b000, 0b001, 0b101, 0b010], // j
+    [0b100, 0b101, 0b110, 0b110, 0b101], // k
+    [0b110, 0b010, 0b010, 0b010, 0b111], // l
+    [0b000, 0b111, 0b111, 0b101, 0b101], // m
+    [0b000, 0b110, 0b101, 0b101, 0b101], // n
+    [0b000, 0b010, 0b101, 0b101, 0b010], // o
+    [0b000, 0b110, 0b101, 0b110, 0b100], // p
+    [0b000, 0b011, 0b101, 0b011, 0b001], // q
+    [0b000, 0b011, 0b100, 0b100, 0b100], // r
+    [0b000, 0b011, 0b110, 0b011, 0b110], // s
+    [0b010, 0b111, 0b010, 0b010, 0b001], // t
+    [0b000, 0b101, 0b101, 0b101, 0b011], // u
+    [0b000, 0b101, 0b101, 0b101, 0b010], // v
+    [0b000, 0b101, 0b101, 0b111, 0b111], // w
+    [0b000, 0b101, 0b010, 0b010, 0b101], // x
+    [0b000, 0b101, 0b011, 0b001, 0b110], // y
+    [0b000, 0b111, 0b011, 0b110, 0b111], // z
+    [0b011, 0b010, 0b110, 0b010, 0b011], // {
+    [0b010, 0b010, 0b010, 0b010, 0b010], // |
+    [0b110, 0b010, 0b011, 0b010, 0b110], // }
+    [0b000, 0b011, 0b110, 0b000, 0b000], // ~
+];
+
+/// glyphs of the 5x7 font from ' ' to '~', one column per value with the top
+/// dot in the lowest bit
+#[rustfmt::skip]
+const ASCII_5X7: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x00, 0x00, 0x5f, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], // #
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1c, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1c, 0x00], // )
+    [0x14, 0x08, 0x3e, 0x08, 0x14], // *
+    [0x08, 0x08, 0x3e, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], // 0
+    [0x00, 0x42, 0x7f, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4b, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7f, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1e], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3e], // @
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], // A
+    [0x7f, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3e, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], // D
+    [0x7f, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7f, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], // G
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], // H
+    [0x00, 0x41, 0x7f, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3f, 0x01], // J
+    [0x7f, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7f, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], // M
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], // N
+    [0x3e, 0x41, 0x41, 0x41, 0x3e], // O
+    [0x7f, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3e, 0x41, 0x51, 0x21, 0x5e], // Q
+    [0x7f, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7f, 0x01, 0x01], // T
+    [0x3f, 0x40, 0x40, 0x40, 0x3f], // U
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], // V
+    [0x3f, 0x40, 0x38, 0x40, 0x3f], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7f, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // backslash
+    [0x00, 0x41, 0x41, 0x7f, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7f, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7f], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7e, 0x09, 0x01, 0x02], // f
+    [0x0c, 0x52, 0x52, 0x52, 0x3e], // g
+    [0x7f, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7d, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3d, 0x00], // j
+    [0x7f, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7f, 0x40, 0x00], // l
+    [0x7c, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7c, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7c, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7c], // q
+    [0x7c, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3f, 0x44, 0x40, 0x20], // t
+    [0x3c, 0x40, 0x40, 0x20, 0x7c], // u
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], // v
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0c, 0x50, 0x50, 0x50, 0x3c], // y
+    [0x44, 0x64, 0x54, 0x4c, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7f, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x10, 0x08, 0x08, 0x10, 0x08], // ~
+];
+
+/// builds a font from glyphs for ' ' to '~' that are `width` by `height`
+/// dots, with one dot of space between characters and lines
+fn ascii_font(width: u32, height: u32, dot: impl Fn(usize, u32, u32) -> bool) -> Font {
+    let glyphs: HashMap<char, Glyph> = (' '..='~')
+        .enumerate()
+        .map(|(i, c)| {
+            let mut glyph = Glyph::from_fn(width, height, |x, y| dot(i, x, y));
+            glyph.advance = width + 1;
+            (c, glyph)
+        })
+        .collect();
+    Font {
+        glyphs,
+        line_height: height + 1,
+    }
+}
+
+pub(super) fn ascii_3x5() -> &'static Font {
+    static FONT: OnceLock<Font> = OnceLock::new();
+    FONT.get_or_init(|| ascii_font(3, 5, |i, x, y| ASCII_3X5[i][y as usize] & (0b100 >> x) != 0))
+}
+
+pub(super) fn ascii_5x7() -> &'static Font {
+    static FONT: OnceLock<Font> = OnceLock::new();
+    FONT.get_or_init(|| ascii_font(5, 7, |i, x, y| ASCII_5X7[i][x as usize] & (1 << y) != 0))
+}
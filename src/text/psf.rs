@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use super::{Font, FontError, Glyph};
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+
+pub(super) fn is_psf(bytes: &[u8]) -> bool {
+    bytes.starts_with(&PSF1_MAGIC) || bytes.starts_with(&PSF2_MAGIC)
+}
+
+/// the layout of a PSF font, with the offsets in bytes
+struct Header {
+    glyphs: usize,
+    glyph_size: usize,
+    width: u32,
+    height: u32,
+    data: usize,
+    has_table: bool,
+}
+
+fn header(bytes: &[u8]) -> Option<Header> {
+    if bytes.starts_with(&PSF1_MAGIC) {
+        let mode = *bytes.get(2)?;
+        let height = *bytes.get(3)?;
+        return Some(Header {
+            glyphs: if mode & 0x01 != 0 { 512 } else { 256 },
+            glyph_size: height as usize,
+            width: 8,
+            height: height as u32,
+            data: 4,
+            has_table: mode & 0x06 != 0,
+        });
+    }
+
+    let field = |i: usize| {
+        let bytes = bytes.get(4 + i * 4..8 + i * 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    };
+    Some(Header {
+        data: field(1)? as usize,
+        has_table: field(2)? & 0x01 != 0,
+        glyphs: field(3)? as usize,
+        glyph_size: field(4)? as usize,
+        height: field(5)?,
+        width: field(6)?,
+    })
+}
+
+/// the characters each glyph is used for, from the unicode table after the
+/// glyphs, where sequences of several characters are skipped
+fn unicode_table(table: &[u8], glyphs: usize, psf2: bool) -> Option<Vec<Vec<char>>> {
+    let mut chars = Vec::with_capacity(glyphs);
+    let mut rest = table;
+    for _ in 0..glyphs {
+        let mut glyph = Vec::new();
+        if psf2 {
+            let end = rest.iter().position(|b| *b == 0xff)?;
+            let single = rest[..end].split(|b| *b == 0xfe).next()?;
+            glyph.extend(std::str::from_utf8(single).ok()?.chars());
+            rest = &rest[end + 1..];
+        } else {
+            let mut sequence = false;
+            loop {
+                let (value, next) = rest.split_first_chunk::<2>()?;
+                rest = next;
+                match u16::from_le_bytes(*value) {
+                    0xffff => break,
+                    0xfffe => sequence = true,
+                    c if !sequence => glyph.extend(char::from_u32(c as u32)),
+                    _ => {}
+                }
+            }
+        }
+        chars.push(glyph);
+    }
+    Some(chars)
+}
+
+pub(super) fn parse(bytes: &[u8]) -> Result<Font, FontError> {
+    let header = header(bytes)
+        .filter(|_| is_psf(bytes))
+        .ok_or(FontError::InvalidPsf)?;
+    let row_size = header.width.div_ceil(8) as usize;
+    if header.width == 0 || header.glyph_size < row_size * header.height as usize {
+        return Err(FontError::InvalidPsf);
+    }
+    let end = header
+        .glyphs
+        .checked_mul(header.glyph_size)
+        .and_then(|size| size.checked_add(header.data))
+        .filter(|end| *end <= bytes.len())
+        .ok_or(FontError::InvalidPsf)?;
+
+    let chars = if header.has_table {
+        let psf2 = bytes.starts_with(&PSF2_MAGIC);
+        unicode_table(&bytes[end..], header.glyphs, psf2).ok_or(FontError::InvalidPsf)?
+    } else {
+        (0..header.glyphs as u32)
+            .map(|i| char::from_u32(i).into_iter().collect())
+            .collect()
+    };
+
+    let mut glyphs = HashMap::new();
+    for (i, chars) in chars.iter().enumerate() {
+        let data = &bytes[header.data + i * header.glyph_size..];
+        let glyph = Glyph::from_fn(header.width, header.height, |x, y| {
+            data[y as usize * row_size + x as usize / 8] & (0x80 >> (x % 8)) != 0
+        });
+        for c in chars {
+            glyphs.insert(*c, glyph.clone());
+        }
+    }
+
+    Ok(Font {
+        glyphs,
+        line_height: header.height,
+    })
+}